@@ -0,0 +1,136 @@
+//! Conditions of the build selecting the default value of a constant.
+
+use std::fmt::Display;
+
+use crate::glob_match;
+
+/// Condition of the build selecting a default value, given to [ConstOptions::profile](crate::ConstOptions::profile)
+/// and [ConstOptions::target](crate::ConstOptions::target).
+/// 
+/// Conditions are evaluated from the environment variables cargo sets for build scripts, so they
+/// describe the build of the crate and not the build of the build script, i.e. the target when
+/// cross-compiling and not the host like `cfg!` would.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// Cargo profile, matching either :
+    /// - `PROFILE`, which is `debug` or `release` depending on the profile the custom ones inherit. `dev`
+    ///   matches `debug`.
+    /// - `opt-level=N` or `debug=V`, matching `OPT_LEVEL` and `DEBUG`.
+    /// 
+    /// Cargo does not give the name of custom profiles to build scripts, so they are rejected by
+    /// [Condition::validate] and must be matched by the profile they inherit or their settings.
+    Profile(String),
+
+    /// Target of the build, matching either :
+    /// - `arch=A`, `os=O` or `pointer_width=N`, matching `CARGO_CFG_TARGET_ARCH`, `CARGO_CFG_TARGET_OS` and
+    ///   `CARGO_CFG_TARGET_POINTER_WIDTH`, i.e. `arch=avr`, `os=none` or `pointer_width=16`.
+    /// - The target triple `TARGET`, as a glob pattern, i.e. `thumbv7em-*`. See [glob_match].
+    Target(String),
+}
+
+impl Condition {
+    /// Check the condition can be matched, profiles being limited to the ones cargo gives build scripts.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Condition::Profile(profile) => match profile.split_once('=') {
+                Some(("opt-level" | "debug", _)) => Ok(()),
+                _ if ["dev", "debug", "release"].contains(&profile.as_str()) => Ok(()),
+                _ => Err(format!("unknown profile `{}`, accepted profiles are dev, debug, release, opt-level=N and debug=V", profile)),
+            },
+            Condition::Target(_) => Ok(()),
+        }
+    }
+
+    /// The condition matches the current build.
+    pub fn matches(&self) -> bool {
+        self.matches_env(&|env_var| std::env::var(env_var).ok())
+    }
+
+    /// The condition matches the build described by environment variables returned by `env`.
+    fn matches_env(&self, env : &dyn Fn(&str) -> Option<String>) -> bool {
+        match self {
+            Condition::Profile(profile) => match profile.split_once('=') {
+                Some(("opt-level", level)) => env("OPT_LEVEL").is_some_and(|value| value == level.trim()),
+                Some(("debug", debug)) => env("DEBUG").is_some_and(|value| value == debug.trim()),
+                _ if profile == "dev" => env("PROFILE").is_some_and(|value| value == "debug"),
+                _ => env("PROFILE").as_deref() == Some(profile),
+            },
+            Condition::Target(target) => match target.split_once('=') {
+                Some((cfg, value)) => env(&format!("CARGO_CFG_TARGET_{}", cfg.trim().trim_start_matches("target_").to_uppercase()))
+                    .is_some_and(|cfg| cfg == value.trim()),
+                None => env("TARGET").is_some_and(|triple| glob_match(target, &triple)),
+            },
+        }
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::Profile(profile) => write!(f, "profile `{}`", profile),
+            Condition::Target(target) => write!(f, "target `{}`", target),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Condition;
+
+    /// Environment of a build script of profile `profile` built with `opt_level`.
+    fn env(profile : &'static str, opt_level : &'static str) -> impl Fn(&str) -> Option<String> {
+        move |env_var| match env_var {
+            "PROFILE" => Some(profile.to_string()),
+            "OPT_LEVEL" => Some(opt_level.to_string()),
+            "DEBUG" => Some((opt_level == "0").to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn profile() {
+        let dev = env("debug", "0");
+        let release = env("release", "3");
+        let custom = env("release", "s");
+
+        let matching = |profile : &str, env : &dyn Fn(&str) -> Option<String>| Condition::Profile(profile.to_string()).matches_env(env);
+        assert!(matching("dev", &dev) && matching("debug", &dev) && !matching("release", &dev));
+        assert!(matching("release", &release) && !matching("dev", &release));
+        assert!(matching("release", &custom) && !matching("bench-opt", &custom) && !matching("dev", &custom));
+        assert!(matching("dev", &|env_var| (env_var == "PROFILE").then(|| "debug".to_string())));
+        assert!(matching("opt-level=0", &dev) && matching("opt-level=3", &release) && matching("opt-level=s", &custom));
+        assert!(matching("debug=true", &dev) && matching("debug=false", &release));
+        assert!(!matching("release", &|_| None));
+        assert_eq!(Condition::Profile("release".to_string()).to_string(), "profile `release`");
+    }
+
+    #[test]
+    fn validate() {
+        for profile in ["dev", "debug", "release", "opt-level=s", "debug=false"] {
+            assert_eq!(Condition::Profile(profile.to_string()).validate(), Ok(()), "{}", profile);
+        }
+
+        assert_eq!(Condition::Profile("bench-opt".to_string()).validate(),
+            Err("unknown profile `bench-opt`, accepted profiles are dev, debug, release, opt-level=N and debug=V".to_string()));
+        assert_eq!(Condition::Target("thumbv7em-*".to_string()).validate(), Ok(()));
+    }
+
+    #[test]
+    fn target() {
+        let avr = |env_var : &str| match env_var {
+            "TARGET" => Some("avr-unknown-gnu-atmega328".to_string()),
+            "CARGO_CFG_TARGET_ARCH" => Some("avr".to_string()),
+            "CARGO_CFG_TARGET_OS" => Some("none".to_string()),
+            "CARGO_CFG_TARGET_POINTER_WIDTH" => Some("16".to_string()),
+            _ => None,
+        };
+
+        let matching = |target : &str| Condition::Target(target.to_string()).matches_env(&avr);
+        assert!(matching("arch=avr") && matching("target_arch = avr") && !matching("arch=arm"));
+        assert!(matching("os=none") && !matching("os=linux"));
+        assert!(matching("pointer_width=16") && !matching("pointer_width=32"));
+        assert!(matching("avr-*") && matching("*-atmega???") && !matching("thumbv7em-*") && !matching("avr"));
+        assert!(!matching("vendor=unknown"));
+        assert_eq!(Condition::Target("os=none".to_string()).to_string(), "target `os=none`");
+    }
+}
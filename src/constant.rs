@@ -0,0 +1,301 @@
+//! Constants declared with [ConstFile::constant](crate::ConstFile::constant), [ConstFile::variant](crate::ConstFile::variant)
+//! and [ConstFile::structure](crate::ConstFile::structure), without macros.
+
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::{ ConstOptions, ConstValue, Declaration, Env, Modifiers, NsocError, Variant, Visibility };
+
+/// Constant of type `T` overridable by the environment variable named after it with the prefix of its
+/// [ConstFile](crate::ConstFile), or set with [Const::env].
+/// 
+/// Builder equivalent of [write_const!](crate::write_const), added to a file with [ConstFile::constant](crate::ConstFile::constant).
+/// ```
+/// use nsoc::{ Const, ConstOptions, Visibility };
+/// 
+/// let constant = Const::new("WORKER_THREADS", 4usize)
+///     .doc("Worker threads")
+///     .visibility(Visibility::Crate)
+///     .options(ConstOptions::default().range(1..=64));
+/// assert_eq!(constant.name(), "WORKER_THREADS");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Const<T> {
+    name : String,
+    default : T,
+    comment : String,
+    modifiers : Modifiers,
+    pub(crate) options : ConstOptions<T>,
+}
+
+impl<T : ConstValue> Const<T> {
+    /// Create constant `name` with its `default` value, public and documented.
+    pub fn new(name : &str, default : T) -> Self {
+        Const { name: name.to_string(), default, comment: String::new(), modifiers: Modifiers::default(), options: ConstOptions::default() }
+    }
+
+    /// Resolve the declaration of the constant, overridden by `env_var` as returned by `env`.
+    pub fn declare(&self, env_var : &str, env : &mut Env) -> Result<Declaration, NsocError> {
+        Declaration::resolve(&self.name, env_var, &self.default, &self.options, self.modifiers, &self.comment, env)
+    }
+}
+
+impl<T> Const<T> {
+    /// Set the description added before the generated documentation.
+    pub fn doc(mut self, comment : &str) -> Self {
+        self.comment = comment.to_string();
+        self
+    }
+
+    /// Set the whole documentation of the constant, like the `cc` modifier.
+    pub fn custom_doc(mut self, comment : &str) -> Self {
+        self.comment = comment.to_string();
+        self.modifiers.custom_comment = true;
+        self
+    }
+
+    /// Write the constant without documentation, like the `nodoc` modifier.
+    pub fn nodoc(mut self) -> Self {
+        self.modifiers.doc = false;
+        self
+    }
+
+    /// Set the visibility of the constant. Default is [Visibility::Public].
+    pub fn visibility(mut self, visibility : Visibility) -> Self {
+        self.modifiers.visibility = visibility;
+        self
+    }
+
+    /// Replace all the modifiers of the constant.
+    pub fn modifiers(mut self, modifiers : Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Set the options of the constant, like the `key = value` options of [write_const!](crate::write_const).
+    /// 
+    /// Replaces all the options, including the environment variable set by [Const::env].
+    pub fn options(mut self, options : ConstOptions<T>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Override the constant with the environment variable `env` instead of its prefixed name.
+    pub fn env(mut self, env : &str) -> Self {
+        self.options = self.options.env(env);
+        self
+    }
+
+    /// Name of the constant.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Environment variable overriding the constant, its name with `prefix` unless [Const::env] is set.
+    pub fn env_var(&self, prefix : &str) -> String {
+        self.options.env.clone().unwrap_or_else(|| format!("{}{}", prefix, self.name))
+    }
+}
+
+impl Const<Variant> {
+    /// Create constant `name` of enum type with its `default` variant, public and documented.
+    /// 
+    /// Builder equivalent of the enum call of [write_const!](crate::write_const), added to a file with
+    /// [ConstFile::variant](crate::ConstFile::variant). Only the `env` and `case_sensitive` options apply, the
+    /// declaration of a constant with other options is invalid.
+    /// ```
+    /// use nsoc::{ Const, Variant };
+    /// 
+    /// let level = Variant::new("Level", &["Debug", "Info", "Warn"], "Info").unwrap();
+    /// let constant = Const::variant("LOG_LEVEL", level).doc("Log level");
+    /// assert_eq!(constant.name(), "LOG_LEVEL");
+    /// ```
+    pub fn variant(name : &str, default : Variant) -> Self {
+        Const { name: name.to_string(), default, comment: String::new(), modifiers: Modifiers::default(), options: ConstOptions::default() }
+    }
+
+    /// Resolve the declaration of the constant, overridden by the variant named in `env_var` as returned by `env`.
+    pub fn declare(&self, env_var : &str, env : &mut Env) -> Result<Declaration, NsocError> {
+        self.options.supported(&["case_sensitive"]).map_err(|reason| NsocError::InvalidDeclaration { constant: self.name.clone(), reason })?;
+        Declaration::variant(&self.name, env_var, &self.default, self.options.case_sensitive, self.modifiers, &self.comment, env)
+    }
+}
+
+/// Resolution of a field of a [ConstStruct], from its environment variable and the delimiter of the struct.
+type Field = Rc<dyn Fn(&str, &str, &mut Env) -> Result<Declaration, NsocError>>;
+
+/// Constant of struct `struct_type` written as a literal, each field being overridden by the environment
+/// variable `{ENV_VAR}_{FIELD}`, where `ENV_VAR` is named after the constant like for [Const].
+/// 
+/// Builder equivalent of the struct call of [write_const!](crate::write_const), added to a file with
+/// [ConstFile::structure](crate::ConstFile::structure).
+/// ```
+/// use nsoc::ConstStruct;
+/// 
+/// let constant = ConstStruct::new("LIMITS", "Limits")
+///     .doc("Connection limits")
+///     .field("max_conn", 10u32)
+///     .field("name", "default");
+/// assert_eq!(constant.name(), "LIMITS");
+/// ```
+#[derive(Clone)]
+pub struct ConstStruct {
+    name : String,
+    struct_type : String,
+    fields : Vec<(String, Field)>,
+    comment : String,
+    modifiers : Modifiers,
+    options : ConstOptions<()>,
+}
+
+impl Debug for ConstStruct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConstStruct")
+            .field("name", &self.name)
+            .field("struct_type", &self.struct_type)
+            .field("fields", &self.fields.iter().map(|(field, _)| field).collect::<Vec<&String>>())
+            .field("comment", &self.comment)
+            .field("modifiers", &self.modifiers)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl ConstStruct {
+    /// Create constant `name` of struct `struct_type` without fields, public and documented.
+    pub fn new(name : &str, struct_type : &str) -> Self {
+        ConstStruct { name: name.to_string(), struct_type: struct_type.to_string(), fields: Vec::new(), comment: String::new(), 
+            modifiers: Modifiers::default(), options: ConstOptions::default() }
+    }
+
+    /// Add field `name` with its `default` value.
+    pub fn field<T : ConstValue + 'static>(mut self, name : &str, default : T) -> Self {
+        self.fields.push((name.to_string(), Rc::new(move |env_var, delimiter, env| 
+            Declaration::resolve(env_var, env_var, &default, &ConstOptions::default().delimiter(delimiter), Modifiers::default(), "", env))));
+        self
+    }
+
+    /// Set the description added before the generated documentation.
+    pub fn doc(mut self, comment : &str) -> Self {
+        self.comment = comment.to_string();
+        self
+    }
+
+    /// Replace all the modifiers of the constant.
+    pub fn modifiers(mut self, modifiers : Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Set the options of the constant. Only the `env` and `delimiter` options apply, the delimiter to all fields,
+    /// and the declaration of a constant with other options is invalid.
+    pub fn options(mut self, options : ConstOptions<()>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Name of the constant.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Environment variable prefixing the variables of the fields, the name of the constant with `prefix`
+    /// unless the `env` option is set.
+    pub fn env_var(&self, prefix : &str) -> String {
+        self.options.env.clone().unwrap_or_else(|| format!("{}{}", prefix, self.name))
+    }
+
+    /// Resolve the declaration of the constant, each field being overridden by `{env_var}_{FIELD}` as returned by `env`.
+    /// 
+    /// Returns the errors of all invalid fields.
+    pub fn declare(&self, env_var : &str, env : &mut Env) -> Result<Declaration, Vec<NsocError>> {
+        self.options.supported(&["delimiter"]).map_err(|reason| vec![NsocError::InvalidDeclaration { constant: self.name.clone(), reason }])?;
+        if self.options.delimiter.is_empty() {
+            return Err(vec![NsocError::InvalidDeclaration { constant: self.name.clone(), reason: "delimiter cannot be empty".to_string() }]);
+        }
+        let fields = self.fields.iter()
+            .map(|(field, resolve)| (field.as_str(), resolve(&format!("{}_{}", env_var, field.to_uppercase()), &self.options.delimiter, env)))
+            .collect();
+        Declaration::structure(&self.name, &self.struct_type, self.modifiers, &self.comment, fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ Const, ConstStruct };
+    use crate::{ ConstOptions, Modifiers, NsocError, Variant, Visibility };
+
+    #[test]
+    fn declare() {
+        let constant = Const::new("DEFAULT_WIDTH", 150usize).doc("Default frame width").visibility(Visibility::Crate);
+        let declaration = constant.declare("DEFAULT_WIDTH", &mut |_| Ok(Some("300".into()))).unwrap();
+
+        assert_eq!(declaration.name, "DEFAULT_WIDTH");
+        assert_eq!(declaration.default, "150");
+        assert_eq!(declaration.value, "300");
+        assert_eq!(declaration.comment, "Default frame width");
+        assert_eq!(declaration.modifiers, Modifiers { visibility: Visibility::Crate, ..Modifiers::default() });
+    }
+
+    #[test]
+    fn documentation() {
+        let declaration = Const::new("A", 1u8).custom_doc("Custom").declare("A", &mut |_| Ok(None)).unwrap();
+        assert!(declaration.modifiers.custom_comment && declaration.modifiers.doc);
+
+        let declaration = Const::new("A", 1u8).nodoc().declare("A", &mut |_| Ok(None)).unwrap();
+        assert_eq!(declaration.to_string(), "pub const A: u8 = 1;\n\n");
+    }
+
+    #[test]
+    fn env_var() {
+        assert_eq!(Const::new("WIDTH", 1u8).env_var("APP_"), "APP_WIDTH");
+        assert_eq!(Const::new("WIDTH", 1u8).env("FRAME_WIDTH").env_var("APP_"), "FRAME_WIDTH");
+
+        let declaration = Const::new("WIDTH", 1u8).declare("APP_WIDTH", &mut |env_var| Ok((env_var == "APP_WIDTH").then(|| "2".into()))).unwrap();
+        assert_eq!(declaration.value, "2");
+        assert_eq!(declaration.overrides, vec![("APP_WIDTH".to_string(), "1".to_string())]);
+    }
+
+    #[test]
+    fn options() {
+        let constant = Const::new("THREADS", 4usize).options(ConstOptions::default().range(1..=64));
+        assert!(matches!(constant.declare("THREADS", &mut |_| Ok(Some("0".into()))), Err(NsocError::InvalidOverride { .. })));
+    }
+
+    #[test]
+    fn variant() {
+        let level = Variant::new("Level", &["Debug", "Info"], "Info").unwrap();
+        let constant = Const::variant("LEVEL", level).options(ConstOptions::default().case_sensitive(true));
+
+        let declaration = constant.declare("LEVEL", &mut |_| Ok(Some("Debug".into()))).unwrap();
+        assert_eq!((declaration.const_type.as_str(), declaration.value.as_str()), ("Level", "Level::Debug"));
+        assert!(matches!(constant.declare("LEVEL", &mut |_| Ok(Some("debug".into()))), Err(NsocError::InvalidOverride { .. })));
+
+        let level = Variant::new("Level", &["Debug", "Info"], "Info").unwrap();
+        let constant = Const::variant("LEVEL", level.clone()).options(ConstOptions::default().profile(("release", level)));
+        assert_eq!(constant.declare("LEVEL", &mut |_| Ok(None)).unwrap_err().to_string(),
+            "Invalid declaration of constant `LEVEL`: option `profile` does not apply, only the `env` and `case_sensitive` options do.");
+    }
+
+    #[test]
+    fn structure() {
+        let constant = ConstStruct::new("LIMITS", "Limits").field("max_conn", 10u32).field("ports", vec![80u16, 443])
+            .options(ConstOptions::default().delimiter(";"));
+        assert_eq!(constant.env_var("APP_"), "APP_LIMITS");
+
+        let declaration = constant.declare("APP_LIMITS", &mut |env_var| Ok((env_var == "APP_LIMITS_PORTS").then(|| "8080;8443".into()))).unwrap();
+        assert_eq!(declaration.value, "Limits { max_conn: 10, ports: &[8080, 8443] }");
+        assert_eq!(declaration.overrides.len(), 2);
+
+        let errors = constant.declare("APP_LIMITS", &mut |_| Ok(Some("many".into()))).unwrap_err();
+        assert_eq!(errors.len(), 2);
+
+        let constant = constant.options(ConstOptions::default().validator(|_| Ok(())));
+        assert_eq!(constant.declare("APP_LIMITS", &mut |_| Ok(None)).unwrap_err().iter().map(NsocError::to_string).collect::<Vec<String>>(),
+            vec!["Invalid declaration of constant `LIMITS`: constraints and validators do not apply, only the `env` and `delimiter` options do."]);
+
+        let constant = constant.options(ConstOptions::default().delimiter(""));
+        assert_eq!(constant.declare("APP_LIMITS", &mut |_| Ok(None)).unwrap_err().iter().map(NsocError::to_string).collect::<Vec<String>>(),
+            vec!["Invalid declaration of constant `LIMITS`: delimiter cannot be empty."]);
+    }
+}
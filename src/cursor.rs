@@ -0,0 +1,58 @@
+//! Cursor over the characters of a document, shared by the parsers of sources.
+
+/// Cursor over the characters of a document.
+/// 
+/// Parsers wrap it and dereference to it, so their grammar reads the document with the same methods.
+pub(crate) struct Cursor {
+    pub(crate) chars : Vec<char>,
+    pub(crate) pos : usize,
+}
+
+impl Cursor {
+    /// Cursor at the start of `content`.
+    pub(crate) fn new(content : &str) -> Self {
+        Cursor { chars: content.chars().collect(), pos: 0 }
+    }
+
+    /// Line of the cursor, starting at 1.
+    pub(crate) fn line(&self) -> usize {
+        self.chars[..self.pos.min(self.chars.len())].iter().filter(|c| **c == '\n').count() + 1
+    }
+
+    /// Error `reason` prefixed with the line of the cursor.
+    pub(crate) fn error(&self, reason : String) -> String {
+        format!("line {}: {}", self.line(), reason)
+    }
+
+    /// Character at the cursor.
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Character at the cursor, moving past it.
+    pub(crate) fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    /// The document continues with `text` at the cursor.
+    pub(crate) fn starts_with(&self, text : &str) -> bool {
+        text.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cursor;
+
+    #[test]
+    fn cursor() {
+        let mut cursor = Cursor::new("a\nbc");
+        assert!(cursor.starts_with("a\nb") && !cursor.starts_with("b"));
+        assert_eq!((cursor.next(), cursor.next(), cursor.peek()), (Some('a'), Some('\n'), Some('b')));
+        assert_eq!(cursor.error("invalid".to_string()), "line 2: invalid");
+        cursor.pos = 10;
+        assert_eq!((cursor.peek(), cursor.line()), (None, 2));
+    }
+}
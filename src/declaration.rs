@@ -0,0 +1,295 @@
+//! Declaration of a constant written in the generated file.
+
+use std::fmt::Display;
+
+use crate::{ const_doc, custom_doc, ConstOptions, ConstValue, Env, Modifiers, NsocError, Override, Variant };
+
+/// Constant declaration written by [write_const!](crate::write_const) once its value is resolved.
+/// 
+/// [Display] writes the documentation followed by the declaration.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Declaration {
+    /// Name of the constant.
+    pub name : String,
+
+    /// Type written in the declaration.
+    pub const_type : String,
+
+    /// Literal of the default value.
+    pub default : String,
+
+    /// Literal of the value written in the declaration.
+    pub value : String,
+
+    /// Environment variables overriding the constant, with their value for the documentation example.
+    pub overrides : Vec<(String, String)>,
+
+    /// Modifiers of the constant.
+    pub modifiers : Modifiers,
+
+    /// Description of the constant, or whole documentation with `cc`.
+    pub comment : String,
+}
+
+impl Declaration {
+    /// Resolve the declaration of constant `name` of type `T` overridden by the environment variable
+    /// `env_var`, as returned by `env`.
+    /// 
+    /// The default is replaced by the first of [ConstOptions::defaults] matching the build. All defaults and
+    /// the overridden value are validated with `options`, whose constraints are added to the description
+    /// with the condition of the chosen default unless the comment is custom.
+    pub fn resolve<T : ConstValue>(name : &str, env_var : &str, default : &T, options : &ConstOptions<T>, modifiers : Modifiers, comment : &str,
+        env : &mut Env) -> Result<Self, NsocError> {
+        if options.delimiter.is_empty() {
+            return Err(NsocError::InvalidDeclaration { constant: name.to_string(), reason: "delimiter cannot be empty".to_string() });
+        }
+        for (condition, _) in &options.defaults {
+            condition.validate().map_err(|reason| NsocError::InvalidDeclaration { constant: name.to_string(), reason })?;
+        }
+        for default in std::iter::once(default).chain(options.defaults.iter().map(|(_, default)| default)) {
+            options.validate(default).map_err(|reason| NsocError::InvalidDeclaration { constant: name.to_string(), 
+                reason: format!("default value `{}` {}", default.to_literal(), reason) })?;
+        }
+
+        let (default, condition) = options.default_value(default);
+        let overrides = default.env_overrides(env_var, &options.delimiter);
+        // Sources of the overrides, reported if the resolved value is invalid.
+        let mut sources : Vec<String> = Vec::new();
+        let value = T::resolve(default, env_var, &options.delimiter, &mut |env_var| env(env_var).inspect(|value| {
+            if let Some(source) = value.as_ref().and_then(|value| value.source.as_ref()).filter(|source| !sources.contains(source)) {
+                sources.push(source.clone());
+            }
+        })).map_err(|err| err.with_constant(name))?;
+        options.validate(&value).map_err(|reason| NsocError::InvalidOverride { constant: name.to_string(), 
+            env_var: overrides.iter().map(|(env_var, _)| env_var.as_str()).collect::<Vec<&str>>().join(", "), 
+            source: (!sources.is_empty()).then(|| sources.join(", ")), 
+            value: value.to_env_delimited(&options.delimiter), expected: T::const_type(), default: default.to_literal(), reason })?;
+
+        let mut comment = comment.to_string();
+        if !modifiers.custom_comment {
+            if let Some(condition) = condition {
+                comment.push_str(&format!("\n\nThe default value is chosen for {}.", condition));
+            }
+            if !options.constraints.is_empty() {
+                let constraints = options.constraints.iter().map(|constraint| format!("- {}", constraint)).collect::<Vec<String>>().join("\n");
+                comment.push_str(&format!("\n\nThe value must be :\n{}", constraints));
+            }
+        }
+
+        Ok(Declaration {
+            name: name.to_string(),
+            const_type: T::const_type(),
+            default: default.to_literal(),
+            value: value.to_literal(),
+            overrides,
+            modifiers,
+            comment,
+        })
+    }
+
+    /// Resolve the declaration of constant `name` of enum type overridden by the name of a variant in the
+    /// environment variable `env_var`, as returned by `env`.
+    /// 
+    /// Accepted variants are added to the description unless the comment is custom.
+    pub fn variant(name : &str, env_var : &str, default : &Variant, case_sensitive : bool, modifiers : Modifiers, comment : &str,
+        env : &mut Env) -> Result<Self, NsocError> {
+        let value = match env(env_var)? {
+            Some(Override { value, source }) => default.parse(&value.to_string(), case_sensitive).map_err(|reason| NsocError::InvalidOverride { constant: name.to_string(), 
+                env_var: env_var.to_string(), source, value: value.to_string(), expected: default.enum_type().to_string(), default: default.to_literal(), reason })?,
+            None => default.clone(),
+        };
+
+        let comment = if modifiers.custom_comment {
+            comment.to_string()
+        } else {
+            let accepted = default.variants().iter().map(|variant| format!("`{}`", variant)).collect::<Vec<String>>().join(", ");
+            format!("{}\n\nAccepted values are {}.", comment, accepted)
+        };
+
+        Ok(Declaration {
+            name: name.to_string(),
+            const_type: default.enum_type().to_string(),
+            default: default.to_literal(),
+            value: value.to_literal(),
+            overrides: vec![(env_var.to_string(), default.name().to_string())],
+            modifiers,
+            comment,
+        })
+    }
+
+    /// Declaration of constant `name` written as a literal of struct `struct_type` from its resolved `fields`.
+    /// 
+    /// Each field is a name with its declaration, usually resolved from the variable `{ENV_VAR}_{FIELD}`.
+    /// Returns the errors of all invalid fields, named `{name}.{field}`.
+    pub fn structure(name : &str, struct_type : &str, modifiers : Modifiers, comment : &str, fields : Vec<(&str, Result<Declaration, NsocError>)>) -> Result<Self, Vec<NsocError>> {
+        let mut errors = Vec::new();
+        let fields : Vec<(&str, Declaration)> = fields.into_iter().filter_map(|(field, declaration)| match declaration {
+            Ok(declaration) => Some((field, declaration)),
+            Err(err) => { errors.push(err.with_constant(&format!("{}.{}", name, field))); None },
+        }).collect();
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let literal = |value : fn(&Declaration) -> &String| if fields.is_empty() {
+            format!("{} {{}}", struct_type)
+        } else {
+            format!("{} {{ {} }}", struct_type, fields.iter().map(|(field, declaration)| format!("{}: {}", field, value(declaration))).collect::<Vec<String>>().join(", "))
+        };
+
+        Ok(Declaration {
+            name: name.to_string(),
+            const_type: struct_type.to_string(),
+            default: literal(|declaration| &declaration.default),
+            value: literal(|declaration| &declaration.value),
+            overrides: fields.iter().flat_map(|(_, declaration)| declaration.overrides.clone()).collect(),
+            modifiers,
+            comment: comment.to_string(),
+        })
+    }
+}
+
+impl Display for Declaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.doc {
+            if self.modifiers.custom_comment {
+                write!(f, "{}", custom_doc(&self.comment))?;
+            } else {
+                write!(f, "{}", const_doc(&self.comment, &self.default, &self.const_type, &self.overrides))?;
+            }
+        }
+
+        write!(f, "{}const {}: {} = {};\n\n", self.modifiers.visibility, self.name, self.const_type, self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Declaration;
+    use crate::{ const_doc, ConstOptions, Modifiers, NsocError, Override, SourceValue, Variant };
+
+    #[test]
+    fn resolve() {
+        let modifiers = "pcrate".parse::<Modifiers>().unwrap();
+        let declaration = Declaration::resolve("SIZE", "SIZE", &(80usize, 50usize), &ConstOptions::default(), modifiers, "Size",
+            &mut |env_var| Ok((env_var == "SIZE_1").then(|| "25".into()))).unwrap();
+
+        assert_eq!(declaration.const_type, "(usize, usize)");
+        assert_eq!(declaration.default, "(80, 50)");
+        assert_eq!(declaration.value, "(80, 25)");
+        assert_eq!(declaration.to_string(), format!("{}pub(crate) const SIZE: (usize, usize) = (80, 25);\n\n", const_doc("Size", "(80, 50)", "(usize, usize)", &declaration.overrides)));
+    }
+
+    #[test]
+    fn structure() {
+        let mut env = |env_var : &str| Ok((env_var == "LIMITS_TIMEOUT_MS").then(|| "10".into()));
+        let fields = vec![
+            ("max_conn", Declaration::resolve("LIMITS_MAX_CONN", "LIMITS_MAX_CONN", &100u32, &ConstOptions::default(), Modifiers::default(), "", &mut env)),
+            ("timeout_ms", Declaration::resolve("LIMITS_TIMEOUT_MS", "LIMITS_TIMEOUT_MS", &5000u64, &ConstOptions::default(), Modifiers::default(), "", &mut env)),
+        ];
+        let declaration = Declaration::structure("LIMITS", "Limits", Modifiers::default(), "", fields).unwrap();
+
+        assert_eq!(declaration.default, "Limits { max_conn: 100, timeout_ms: 5000 }");
+        assert_eq!(declaration.value, "Limits { max_conn: 100, timeout_ms: 10 }");
+        assert_eq!(declaration.overrides, vec![("LIMITS_MAX_CONN".to_string(), "100".to_string()), ("LIMITS_TIMEOUT_MS".to_string(), "5000".to_string())]);
+        assert_eq!(Declaration::structure("UNIT", "Unit", Modifiers::default(), "", vec![]).unwrap().value, "Unit {}");
+    }
+
+    #[test]
+    fn structure_errors() {
+        let mut env = |_ : &str| Ok(Some("x".into()));
+        let fields = vec![
+            ("max_conn", Declaration::resolve("LIMITS_MAX_CONN", "LIMITS_MAX_CONN", &100u32, &ConstOptions::default(), Modifiers::default(), "", &mut env)),
+            ("name", Declaration::resolve("LIMITS_NAME", "LIMITS_NAME", &"a", &ConstOptions::default(), Modifiers::default(), "", &mut env)),
+            ("timeout_ms", Declaration::resolve("LIMITS_TIMEOUT_MS", "LIMITS_TIMEOUT_MS", &5000u64, &ConstOptions::default(), Modifiers::default(), "", &mut env)),
+        ];
+        let errors = Declaration::structure("LIMITS", "Limits", Modifiers::default(), "", fields).unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], NsocError::InvalidOverride { constant, env_var, .. } if constant == "LIMITS.max_conn" && env_var == "LIMITS_MAX_CONN"));
+        assert!(matches!(&errors[1], NsocError::InvalidOverride { constant, .. } if constant == "LIMITS.timeout_ms"));
+    }
+
+    #[test]
+    fn variant() {
+        let default = Variant::new("Level", &["Debug", "Info"], "Info").unwrap();
+        let declaration = Declaration::variant("LOG_LEVEL", "LOG_LEVEL", &default, false, Modifiers::default(), "Log level", &mut |_| Ok(Some("debug".into()))).unwrap();
+
+        assert_eq!(declaration.const_type, "Level");
+        assert_eq!(declaration.default, "Level::Info");
+        assert_eq!(declaration.value, "Level::Debug");
+        assert_eq!(declaration.comment, "Log level\n\nAccepted values are `Debug`, `Info`.");
+        assert_eq!(declaration.overrides, vec![("LOG_LEVEL".to_string(), "Info".to_string())]);
+
+        assert_eq!(Declaration::variant("LOG_LEVEL", "LOG_LEVEL", &default, true, Modifiers::default(), "", &mut |_| Ok(Some("debug".into()))).unwrap_err().to_string(),
+            "Invalid value `debug` of environment variable `LOG_LEVEL` for constant `LOG_LEVEL`: unknown variant `debug` of `Level`, accepted variants are Debug, Info. Expected `Level`, default is `Level::Info`.");
+    }
+
+    #[test]
+    fn resolve_validated() {
+        let options = ConstOptions::default().range(1..=64).power_of_two(true);
+        let declaration = Declaration::resolve("THREADS", "THREADS", &4usize, &options, Modifiers::default(), "Threads", &mut |_| Ok(Some("8".into()))).unwrap();
+        assert_eq!(declaration.value, "8");
+        assert_eq!(declaration.comment, "Threads\n\nThe value must be :\n- >= 1 and <= 64\n- a power of two");
+
+        assert_eq!(Declaration::resolve("THREADS", "THREADS", &4usize, &options, Modifiers::default(), "", &mut |_| Ok(Some("128".into()))),
+            Err(NsocError::InvalidOverride { constant: "THREADS".to_string(), env_var: "THREADS".to_string(), source: None, value: "128".to_string(), 
+                expected: "usize".to_string(), default: "4".to_string(), reason: "must be >= 1 and <= 64".to_string() }));
+        assert_eq!(Declaration::resolve("THREADS", "THREADS", &3usize, &options, Modifiers::default(), "", &mut |_| Ok(None)),
+            Err(NsocError::InvalidDeclaration { constant: "THREADS".to_string(), reason: "default value `3` must be a power of two".to_string() }));
+    }
+
+    #[test]
+    fn resolve_profile_default() {
+        std::env::set_var("DEBUG", "nsoc-declaration");
+        let options = ConstOptions::default().profile(("release", 10_000usize)).profile(("debug=nsoc-declaration", 500)).range(1..);
+        let declaration = Declaration::resolve("LOG_BUFFER", "LOG_BUFFER", &10, &options, Modifiers::default(), "Log buffer", &mut |_| Ok(None)).unwrap();
+        assert_eq!((declaration.default.as_str(), declaration.value.as_str()), ("500", "500"));
+        assert_eq!(declaration.comment, "Log buffer\n\nThe default value is chosen for profile `debug=nsoc-declaration`.\n\nThe value must be :\n- >= 1");
+
+        // Overrides still have precedence.
+        let declaration = Declaration::resolve("LOG_BUFFER", "LOG_BUFFER", &10, &options, Modifiers::default(), "", &mut |_| Ok(Some("42".into()))).unwrap();
+        assert_eq!(declaration.value, "42");
+
+        // Defaults of other profiles are validated too.
+        assert_eq!(Declaration::resolve("LOG_BUFFER", "LOG_BUFFER", &10, &options.clone().profile(("dev", 0)), Modifiers::default(), "", &mut |_| Ok(None)),
+            Err(NsocError::InvalidDeclaration { constant: "LOG_BUFFER".to_string(), reason: "default value `0` must be >= 1".to_string() }));
+
+        assert_eq!(Declaration::resolve("PORTS", "PORTS", &vec![80u16], &ConstOptions::default().delimiter(""), Modifiers::default(), "", &mut |_| Ok(None)),
+            Err(NsocError::InvalidDeclaration { constant: "PORTS".to_string(), reason: "delimiter cannot be empty".to_string() }));
+
+        // Custom profiles cannot be matched.
+        assert_eq!(Declaration::resolve("LOG_BUFFER", "LOG_BUFFER", &10, &options.profile(("bench", 1)), Modifiers::default(), "", &mut |_| Ok(None)),
+            Err(NsocError::InvalidDeclaration { constant: "LOG_BUFFER".to_string(), 
+                reason: "unknown profile `bench`, accepted profiles are dev, debug, release, opt-level=N and debug=V".to_string() }));
+    }
+
+    #[test]
+    fn resolve_source() {
+        let options = ConstOptions::default().range(1..);
+        let mut env = |_ : &str| Ok(Some(Override { value: SourceValue::Value("0".to_string()), source: Some("nsoc.toml".to_string()) }));
+        assert_eq!(Declaration::resolve("COUNT", "COUNT", &1u8, &options, Modifiers::default(), "", &mut env).unwrap_err().to_string(),
+            "Invalid value `0` of `COUNT` in `nsoc.toml` for constant `COUNT`: must be >= 1. Expected `u8`, default is `1`.");
+
+        let mut env = |_ : &str| Ok(Some(Override { value: SourceValue::Value("wide".to_string()), source: Some(".env".to_string()) }));
+        assert_eq!(Declaration::resolve("COUNT", "COUNT", &1u8, &options, Modifiers::default(), "", &mut env).unwrap_err().to_string(),
+            "Invalid value `wide` of `COUNT` in `.env` for constant `COUNT`: invalid digit found in string. Expected `u8`, default is `1`.");
+    }
+
+    #[test]
+    fn resolve_env_var() {
+        let declaration = Declaration::resolve("WIDTH", "APP_WIDTH", &(1u8, 2u8), &ConstOptions::default(), Modifiers::default(), "",
+            &mut |env_var| Ok((env_var == "APP_WIDTH_0").then(|| "3".into()))).unwrap();
+        assert_eq!(declaration.name, "WIDTH");
+        assert_eq!(declaration.value, "(3, 2)");
+        assert_eq!(declaration.overrides, vec![("APP_WIDTH_0".to_string(), "1".to_string()), ("APP_WIDTH_1".to_string(), "2".to_string())]);
+    }
+
+    #[test]
+    fn resolve_error() {
+        assert_eq!(Declaration::resolve("COUNT", "COUNT", &1u8, &ConstOptions::default(), Modifiers::default(), "", &mut |_| Ok(Some("-1".into()))),
+            Err(NsocError::InvalidOverride { constant: "COUNT".to_string(), env_var: "COUNT".to_string(), source: None, value: "-1".to_string(), 
+                expected: "u8".to_string(), default: "1".to_string(), reason: "invalid digit found in string".to_string() }));
+    }
+}
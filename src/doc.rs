@@ -0,0 +1,162 @@
+//! Documentation generated for each constant written by [write_const!](crate::write_const).
+
+use std::fmt::Write;
+
+/// Generate the documentation lines of an overridable constant.
+/// 
+/// The documentation contains the `description`, the `default` value, the `const_type`, the
+/// environment variables that override it and an example of `cargo build` setting them.
+/// `overrides` contains each environment variable name with the value set in the example.
+/// 
+/// # Example
+/// ```
+/// let doc = nsoc::const_doc("Default frame width", "150", "usize", &[("DEFAULT_WIDTH".to_string(), "150".to_string())]);
+/// assert!(doc.starts_with("/// Default frame width\n///\n/// # Default\n/// `150`\n"));
+/// assert!(doc.contains("/// DEFAULT_WIDTH=150 cargo build\n"));
+/// ```
+pub fn const_doc(description : &str, default : &str, const_type : &str, overrides : &[(String, String)]) -> String {
+    let mut doc = String::new();
+
+    if !description.trim().is_empty() {
+        doc.push_str(&custom_doc(description));
+        doc.push_str("///\n");
+    }
+
+    writeln!(doc, "/// # Default\n/// `{}`\n///", default).unwrap();
+    writeln!(doc, "/// # Type\n/// `{}`\n///", const_type).unwrap();
+
+    let env_vars = overrides.iter().map(|(env_var, _)| format!("`{}`", env_var)).collect::<Vec<String>>().join(", ");
+    let plural = if overrides.len() > 1 { "s" } else { "" };
+    writeln!(doc, "/// # Override\n/// The default value can be overwritten when compiling with the {} environment variable{}.\n///", env_vars, plural).unwrap();
+
+    let settings = overrides.iter().map(|(env_var, example)| format!("`{}` set to `{}`", env_var, example.escape_debug())).collect::<Vec<String>>().join(", ");
+    let command = overrides.iter().map(|(env_var, example)| format!("{}={} ", env_var, shell_quote(example))).collect::<String>();
+    writeln!(doc, "/// # Example\n/// This will build with {}.\n/// ```sh\n/// {}cargo build\n/// ```", settings, command).unwrap();
+
+    doc
+}
+
+/// Generate documentation lines from a custom comment, each line of `comment` being a doc line.
+/// 
+/// The indentation common to the lines is removed, so nested lists and code blocks keep their own.
+/// The first line is ignored when computing it if it is not indented, as it usually follows the
+/// opening quote of the comment.
+/// 
+/// # Example
+/// ```
+/// assert_eq!(nsoc::custom_doc("First line\n    - Item\n      continued"), "/// First line\n/// - Item\n///   continued\n");
+/// ```
+pub fn custom_doc(comment : &str) -> String {
+    let lines : Vec<&str> = comment.trim_end().lines().skip_while(|line| line.trim().is_empty()).collect();
+    let indentation = |line : &str| line.chars().take_while(|c| c.is_whitespace()).count();
+    let common = lines.iter().enumerate()
+        .filter(|(index, line)| !line.trim().is_empty() && (*index > 0 || indentation(line) > 0))
+        .map(|(_, line)| indentation(line))
+        .min()
+        .unwrap_or(0);
+
+    lines.iter().fold(String::new(), |mut doc, line| {
+        let line : String = line.chars().skip(common.min(indentation(line))).collect();
+        if line.trim().is_empty() {
+            doc.push_str("///\n");
+        } else {
+            writeln!(doc, "/// {}", line.trim_end()).unwrap();
+        }
+        doc
+    })
+}
+
+/// Quote `value` for the shell if it contains characters other than letters, digits and `_.,:/+-`.
+/// 
+/// Values with control characters use `$'...'` quoting so the example stays on one line.
+fn shell_quote(value : &str) -> String {
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "_.,:/+-".contains(c)) {
+        value.to_string()
+    } else if value.chars().any(char::is_control) {
+        let escaped = value.chars().fold(String::new(), |mut escaped, c| {
+            match c {
+                '\n' => escaped.push_str("\\n"),
+                '\t' => escaped.push_str("\\t"),
+                '\r' => escaped.push_str("\\r"),
+                '\\' | '\'' => { escaped.push('\\'); escaped.push(c) },
+                c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+                c => escaped.push(c),
+            }
+            escaped
+        });
+        format!("$'{}'", escaped)
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{const_doc, custom_doc, shell_quote};
+
+    /// Overrides for a single environment variable.
+    fn single(env_var : &str, example : &str) -> Vec<(String, String)> {
+        vec![(env_var.to_string(), example.to_string())]
+    }
+
+    #[test]
+    fn template() {
+        assert_eq!(const_doc("Maximum count of entry the log can contain at once.", "50", "usize", &single("NSLOG_ENTRY_COUNT", "50")),
+"/// Maximum count of entry the log can contain at once.
+///
+/// # Default
+/// `50`
+///
+/// # Type
+/// `usize`
+///
+/// # Override
+/// The default value can be overwritten when compiling with the `NSLOG_ENTRY_COUNT` environment variable.
+///
+/// # Example
+/// This will build with `NSLOG_ENTRY_COUNT` set to `50`.
+/// ```sh
+/// NSLOG_ENTRY_COUNT=50 cargo build
+/// ```
+");
+    }
+
+    #[test]
+    fn template_without_description() {
+        assert!(const_doc("", "1", "u8", &single("DEPTH", "1")).starts_with("/// # Default\n/// `1`\n///\n"));
+    }
+
+    #[test]
+    fn template_string() {
+        let doc = const_doc("Application name", "\"my app\"", "&'static str", &single("APP_NAME", "my app"));
+        assert!(doc.contains("/// `\"my app\"`\n"));
+        assert!(doc.contains("/// This will build with `APP_NAME` set to `my app`.\n"));
+        assert!(doc.contains("/// APP_NAME='my app' cargo build\n"));
+    }
+
+    #[test]
+    fn template_many_overrides() {
+        let doc = const_doc("", "(80, 50)", "(usize, usize)", &[("SIZE_0".to_string(), "80".to_string()), ("SIZE_1".to_string(), "50".to_string())]);
+        assert!(doc.contains("/// The default value can be overwritten when compiling with the `SIZE_0`, `SIZE_1` environment variables.\n"));
+        assert!(doc.contains("/// This will build with `SIZE_0` set to `80`, `SIZE_1` set to `50`.\n"));
+        assert!(doc.contains("/// SIZE_0=80 SIZE_1=50 cargo build\n"));
+    }
+
+    #[test]
+    fn quote() {
+        assert_eq!(shell_quote("https://nickelange.studio/a-b_c.d"), "https://nickelange.studio/a-b_c.d");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote("it's\n\tC:\\\0"), "$'it\\'s\\n\\tC:\\\\\\u0000'");
+    }
+
+    #[test]
+    fn custom() {
+        assert_eq!(custom_doc(""), "");
+        assert_eq!(custom_doc("\n  Maximum count.\n\n  # Note\n  Be careful.\n"), "/// Maximum count.\n///\n/// # Note\n/// Be careful.\n");
+        assert_eq!(custom_doc("\n    Levels :\n    - Debug\n\n    ```\n    if ready {\n        run();\n    }\n    ```"),
+            "/// Levels :\n/// - Debug\n///\n/// ```\n/// if ready {\n///     run();\n/// }\n/// ```\n");
+        assert_eq!(custom_doc("Maximum count.\n        Be careful.\n          Really."), "/// Maximum count.\n/// Be careful.\n///   Really.\n");
+        assert_eq!(custom_doc("  Maximum count.\n    Be careful."), "/// Maximum count.\n///   Be careful.\n");
+    }
+}
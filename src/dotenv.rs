@@ -0,0 +1,137 @@
+//! Parser of `.env` files reading override values into a [Source].
+//! 
+//! Each line is `KEY=value`, optionally prefixed by `export`. Lines starting with `#` are comments.
+//! Values can be unquoted, with an optional comment after ` #`, single-quoted without escapes or
+//! double-quoted with `\n`, `\t`, `\r`, `\"` and `\\` escapes. Quoted values can span lines. Variables
+//! are not expanded.
+
+use crate::{ Source, SourceValue };
+
+/// Parse the `.env` file `content` into `source`.
+pub fn parse_dotenv(content : &str, source : &mut Source) -> Result<(), String> {
+    let mut lines = content.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |reason : &str| format!("line {}: {}", index + 1, reason);
+        let line = line.strip_prefix("export ").map(str::trim_start).unwrap_or(line);
+        let (key, value) = line.split_once('=').ok_or_else(|| error("expected `=` after key"))?;
+        let key = key.trim_end();
+
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')) {
+            return Err(error(&format!("invalid key `{}`", key)));
+        }
+
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                // Quoted values continue on the next lines until the closing quote.
+                let mut quoted = value[1..].to_string();
+                let end = loop {
+                    if let Some(end) = closing_quote(&quoted, quote) {
+                        break end;
+                    }
+
+                    match lines.next() {
+                        Some((_, next)) => { quoted.push('\n'); quoted.push_str(next); },
+                        None => return Err(error("unterminated quoted value")),
+                    }
+                };
+
+                let rest = quoted[end + 1..].trim();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    return Err(error(&format!("unexpected `{}` after quoted value", rest)));
+                }
+
+                if quote == '"' { unescape(&quoted[..end]) } else { quoted[..end].to_string() }
+            },
+            _ => match value.find(" #") {
+                Some(comment) => value[..comment].trim_end().to_string(),
+                None => value.trim_end().to_string(),
+            },
+        };
+
+        source.insert(key, SourceValue::Value(value));
+    }
+
+    Ok(())
+}
+
+/// Position of the unescaped `quote` closing a value.
+fn closing_quote(value : &str, quote : char) -> Option<usize> {
+    let mut escaped = false;
+
+    for (position, c) in value.char_indices() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(position),
+            _ => escaped = false,
+        }
+    }
+
+    None
+}
+
+/// Replace the escapes of a double-quoted value.
+fn unescape(value : &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(c @ ('"' | '\\')) => unescaped.push(c),
+            Some(c) => { unescaped.push('\\'); unescaped.push(c); },
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_dotenv;
+    use crate::{ Source, SourceValue };
+
+    #[test]
+    fn values() {
+        let source = Source::parse(".env", "# Local settings\n\
+            \n\
+            NSLOG_DEFAULT_WIDTH=300\n\
+            export NAME = nsoc # Name\n\
+            URL=https://nickelange.studio/#top\n\
+            SINGLE='C:\\path # not a comment'\n\
+            DOUBLE=\"quoted \\\"value\\\"\\tand\\ttabs\" # Comment\n\
+            MULTI=\"first\n  second\"\n\
+            EMPTY=\n", parse_dotenv).unwrap();
+
+        let get = |key| source.get(key).map(SourceValue::to_string);
+        assert_eq!(get("NSLOG_DEFAULT_WIDTH").as_deref(), Some("300"));
+        assert_eq!(get("NAME").as_deref(), Some("nsoc"));
+        assert_eq!(get("URL").as_deref(), Some("https://nickelange.studio/#top"));
+        assert_eq!(get("SINGLE").as_deref(), Some("C:\\path # not a comment"));
+        assert_eq!(get("DOUBLE").as_deref(), Some("quoted \"value\"\tand\ttabs"));
+        assert_eq!(get("MULTI").as_deref(), Some("first\n  second"));
+        assert_eq!(get("EMPTY").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Source::parse(".env", "A=1\nB", parse_dotenv).unwrap_err(), "line 2: expected `=` after key");
+        assert_eq!(Source::parse(".env", "MY KEY=1", parse_dotenv).unwrap_err(), "line 1: invalid key `MY KEY`");
+        assert_eq!(Source::parse(".env", "\nA=\"open\nstill open", parse_dotenv).unwrap_err(), "line 2: unterminated quoted value");
+        assert_eq!(Source::parse(".env", "A='a' b", parse_dotenv).unwrap_err(), "line 1: unexpected `b` after quoted value");
+    }
+}
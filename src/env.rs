@@ -0,0 +1,74 @@
+//! Environment variables overriding constants.
+
+use crate::{ NsocError, SourceValue };
+
+/// Function returning the value overriding a constant resolved from an environment variable, `None` if not set.
+/// 
+/// [ConstFile::lookup](crate::ConstFile::lookup) is used by [write_const!](crate::write_const).
+pub type Env<'a> = dyn FnMut(&str) -> Result<Option<Override>, NsocError> + 'a;
+
+/// Value overriding a constant, returned by [Env].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    /// Value of the environment variable, or of the key in a [Source](crate::Source) where arrays
+    /// keep their elements.
+    pub value : SourceValue,
+
+    /// Name of the [Source](crate::Source) the value was read from, `None` for the environment variable.
+    pub source : Option<String>,
+}
+
+impl From<String> for Override {
+    /// Value of an environment variable.
+    fn from(value : String) -> Self {
+        Override { value: SourceValue::Value(value), source: None }
+    }
+}
+
+impl From<&str> for Override {
+    /// Value of an environment variable.
+    fn from(value : &str) -> Self {
+        Override::from(value.to_string())
+    }
+}
+
+/// Get the value of the environment variable `env_var` overriding a constant.
+/// 
+/// The variable is read when the build script runs, not when it is compiled like with
+/// [option_env!], so changing it is enough to get new constants. `cargo:rerun-if-env-changed`
+/// is emitted so cargo runs the build script again when the variable changes.
+/// 
+/// Returns [NsocError::NotUnicode] if the variable is set but is not valid unicode.
+pub fn env_override(env_var : &str) -> Result<Option<String>, NsocError> {
+    println!("cargo:rerun-if-env-changed={}", env_var);
+
+    std::env::var_os(env_var).map(|value| value.into_string()
+        .map_err(|value| NsocError::NotUnicode { env_var: env_var.to_string(), value: value.to_string_lossy().to_string() }))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::env_override;
+
+    #[test]
+    fn read_at_run_time() {
+        assert_eq!(env_override("NSOC_ENV_TEST_UNSET"), Ok(None));
+
+        std::env::set_var("NSOC_ENV_TEST_SET", "");
+        assert_eq!(env_override("NSOC_ENV_TEST_SET"), Ok(Some("".to_string())));
+
+        std::env::set_var("NSOC_ENV_TEST_SET", "42");
+        assert_eq!(env_override("NSOC_ENV_TEST_SET"), Ok(Some("42".to_string())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn not_unicode() {
+        use std::os::unix::ffi::OsStrExt;
+        use crate::NsocError;
+
+        std::env::set_var("NSOC_ENV_TEST_INVALID", std::ffi::OsStr::from_bytes(&[0x66, 0x80]));
+        assert_eq!(env_override("NSOC_ENV_TEST_INVALID"), Err(NsocError::NotUnicode { env_var: "NSOC_ENV_TEST_INVALID".to_string(), value: "f\u{FFFD}".to_string() }));
+    }
+}
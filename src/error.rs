@@ -0,0 +1,155 @@
+//! Errors of constants generation.
+
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// Error while generating constants.
+/// 
+/// Errors are collected by [ConstFile](crate::ConstFile) and reported together when the file is
+/// written. See [report].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NsocError {
+    /// Value of an environment variable or of a source cannot be used for a constant.
+    InvalidOverride {
+        /// Name of the constant, with the field name for struct literals.
+        constant : String,
+
+        /// Environment variable overriding the constant, or key of the value in the source.
+        env_var : String,
+
+        /// Name of the [Source](crate::Source) the value was read from, `None` for the environment variable.
+        source : Option<String>,
+
+        /// Value of the environment variable or of the key in the source.
+        value : String,
+
+        /// Type expected for the value.
+        expected : String,
+
+        /// Literal of the default value.
+        default : String,
+
+        /// Why the value cannot be used.
+        reason : String,
+    },
+
+    /// Declaration of a constant is invalid, i.e. unknown modifiers.
+    InvalidDeclaration {
+        /// Name of the constant.
+        constant : String,
+
+        /// Why the declaration is invalid.
+        reason : String,
+    },
+
+    /// Environment variable is set but is not valid unicode.
+    NotUnicode {
+        /// Environment variable name.
+        env_var : String,
+
+        /// Value with invalid characters replaced.
+        value : String,
+    },
+
+    /// Source of override values could not be read or parsed.
+    InvalidSource {
+        /// Path of the file.
+        path : PathBuf,
+
+        /// Why the file is invalid.
+        reason : String,
+    },
+
+    /// File could not be written.
+    Io {
+        /// Path of the file.
+        path : PathBuf,
+
+        /// Error description.
+        reason : String,
+    },
+}
+
+impl NsocError {
+    /// Set the constant name of [NsocError::InvalidOverride] and [NsocError::InvalidDeclaration].
+    pub fn with_constant(self, name : &str) -> Self {
+        match self {
+            NsocError::InvalidOverride { env_var, source, value, expected, default, reason, .. } => 
+                NsocError::InvalidOverride { constant: name.to_string(), env_var, source, value, expected, default, reason },
+            NsocError::InvalidDeclaration { reason, .. } => NsocError::InvalidDeclaration { constant: name.to_string(), reason },
+            error => error,
+        }
+    }
+}
+
+impl Display for NsocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NsocError::InvalidOverride { constant, env_var, source, value, expected, default, reason } => {
+                match source {
+                    Some(source) => write!(f, "Invalid value `{}` of `{}` in `{}`", value.escape_debug(), env_var, source)?,
+                    None => write!(f, "Invalid value `{}` of environment variable `{}`", value.escape_debug(), env_var)?,
+                }
+                write!(f, " for constant `{}`: {}. Expected `{}`, default is `{}`.", constant, reason, expected, default)
+            },
+            NsocError::InvalidDeclaration { constant, reason } => write!(f, "Invalid declaration of constant `{}`: {}.", constant, reason),
+            NsocError::NotUnicode { env_var, value } => write!(f, "Environment variable `{}` is not valid unicode: `{}`.", env_var, value.escape_debug()),
+            NsocError::InvalidSource { path, reason } => write!(f, "Could not read override source `{}`: {}.", path.display(), reason),
+            NsocError::Io { path, reason } => write!(f, "Could not write file `{}`: {}.", path.display(), reason),
+        }
+    }
+}
+
+impl std::error::Error for NsocError {}
+
+/// Report `errors` to cargo with `cargo:warning=` and fail the build with all of them.
+/// 
+/// # Panics
+/// Always panics, listing all errors.
+pub fn report(errors : &[NsocError]) -> ! {
+    for error in errors {
+        println!("cargo:warning={}", error);
+    }
+
+    panic!("{} error(s) while generating constants:\n{}", errors.len(), 
+        errors.iter().map(|error| format!("  - {}", error)).collect::<Vec<String>>().join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{report, NsocError};
+
+    /// Override error of constant `WIDTH`.
+    fn invalid_width() -> NsocError {
+        NsocError::InvalidOverride { constant: "WIDTH".to_string(), env_var: "WIDTH".to_string(), source: None, value: "wide\n".to_string(),
+            expected: "usize".to_string(), default: "150".to_string(), reason: "invalid digit found in string".to_string() }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(invalid_width().to_string(), 
+            "Invalid value `wide\\n` of environment variable `WIDTH` for constant `WIDTH`: invalid digit found in string. Expected `usize`, default is `150`.");
+        assert_eq!(NsocError::InvalidOverride { constant: "WIDTH".to_string(), env_var: "DEFAULT_WIDTH".to_string(), source: Some("nsoc.toml".to_string()), 
+            value: "wide".to_string(), expected: "usize".to_string(), default: "150".to_string(), reason: "invalid digit found in string".to_string() }.to_string(), 
+            "Invalid value `wide` of `DEFAULT_WIDTH` in `nsoc.toml` for constant `WIDTH`: invalid digit found in string. Expected `usize`, default is `150`.");
+        assert_eq!(NsocError::InvalidDeclaration { constant: "A".to_string(), reason: "unknown".to_string() }.to_string(), 
+            "Invalid declaration of constant `A`: unknown.");
+        assert_eq!(NsocError::InvalidSource { path: "nsoc.toml".into(), reason: "line 1: expected value".to_string() }.to_string(), 
+            "Could not read override source `nsoc.toml`: line 1: expected value.");
+    }
+
+    #[test]
+    fn with_constant() {
+        assert_eq!(invalid_width().with_constant("SIZE.width"), NsocError::InvalidOverride { constant: "SIZE.width".to_string(), env_var: "WIDTH".to_string(), source: None, 
+            value: "wide\n".to_string(), expected: "usize".to_string(), default: "150".to_string(), reason: "invalid digit found in string".to_string() });
+
+        let error = NsocError::NotUnicode { env_var: "A".to_string(), value: "\u{FFFD}".to_string() };
+        assert_eq!(error.clone().with_constant("B"), error);
+    }
+
+    #[test]
+    #[should_panic(expected = "2 error(s) while generating constants:\n  - Invalid value `wide\\n` of environment variable `WIDTH`")]
+    fn report_all() {
+        report(&[invalid_width(), NsocError::InvalidDeclaration { constant: "A".to_string(), reason: "unknown".to_string() }]);
+    }
+}
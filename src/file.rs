@@ -0,0 +1,425 @@
+//! File of constants generated in the `OUT_DIR`.
+
+use std::path::{ Path, PathBuf };
+
+use crate::{ env_override, Const, ConstStruct, ConstValue, Declaration, Env, NsocError, Override, Source, Variant };
+use crate::source::package_path;
+
+/// File of constants generated in the `OUT_DIR`, used as file handle by [write_file!](crate::write_file).
+/// 
+/// Declarations and errors are collected until the file is written, so all invalid constants are
+/// reported at once.
+/// 
+/// Constants are overridden by environment variables named after them with the [prefix](ConstFile::prefix)
+/// of the file, by default the package name in upper case followed by `_`, i.e. `NSLOG_DEFAULT_WIDTH` for
+/// constant `DEFAULT_WIDTH` of package `nslog`.
+/// 
+/// Constants without environment variable are then looked up in the [sources](Source) of the file, i.e.
+/// configuration files added with `ConstFile::toml`, `ConstFile::dotenv` or `ConstFile::json`, in the
+/// order they were added, before using the default. YAML and RON files are read with the `yaml` and
+/// `ron` features.
+/// 
+/// # Example
+/// In `build.rs`, constants can be added without macros, in loops or by helper functions.
+/// ```no_run
+/// use nsoc::{ Const, ConstFile, NsocError, Visibility };
+/// 
+/// fn frame(file : &mut ConstFile) {
+///     for (name, default) in [("DEFAULT_WIDTH", 150usize), ("DEFAULT_HEIGHT", 50)] {
+///         file.constant(Const::new(name, default).doc("Default frame size"));
+///     }
+/// }
+/// 
+/// fn main() -> Result<(), Vec<NsocError>> {
+///     let mut file = ConstFile::new("config");
+///     frame(&mut file);
+///     file.constant(Const::new("MAX_ENTRY", 100u32).visibility(Visibility::Crate)).write()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstFile {
+    name : String,
+    prefix : String,
+    sources : Vec<Source>,
+    watch_missing : bool,
+    #[cfg(feature = "toml")]
+    config_read : bool,
+    out_dir : Option<PathBuf>,
+    names : Vec<String>,
+    content : String,
+    errors : Vec<NsocError>,
+}
+
+impl ConstFile {
+    /// Create the file `{name}.rs`. Nothing is written until [ConstFile::write].
+    pub fn new(name : &str) -> Self {
+        let prefix = std::env::var("CARGO_PKG_NAME").map(|package| format!("{}_", package.to_uppercase().replace('-', "_"))).unwrap_or_default();
+        ConstFile { name: name.to_string(), prefix, sources: Vec::new(), watch_missing: false,
+            #[cfg(feature = "toml")]
+            config_read: false,
+            out_dir: None, names: Vec::new(), content: String::new(), errors: Vec::new() }
+    }
+
+    /// Set the prefix of the environment variables overriding constants, which can be empty.
+    pub fn prefix(&mut self, prefix : &str) -> &mut Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Environment variable overriding constant `name`, i.e. `name` with the prefix of the file.
+    pub fn env_var(&self, name : &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    /// Write the file in `out_dir` instead of the `OUT_DIR` of the build script.
+    pub fn out_dir(&mut self, out_dir : impl Into<PathBuf>) -> &mut Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Add a source of override values, looked up after the environment variables and the sources
+    /// added before.
+    pub fn source(&mut self, source : Source) -> &mut Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Sources of override values added so far.
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// Watch the source files added after this call even while they are missing, so creating one
+    /// rebuilds the crate. Disabled by default.
+    /// 
+    /// Cargo reruns the build script on every build while a watched file is missing, so only enable
+    /// it for files expected to be created.
+    pub fn watch_missing(&mut self, watch_missing : bool) -> &mut Self {
+        self.watch_missing = watch_missing;
+        self
+    }
+
+    /// Add the file at `path` as a source parsed by `parse`, relative to the package root.
+    /// 
+    /// A missing file is ignored and only watched with [ConstFile::watch_missing], errors while reading
+    /// or parsing it are added to the file. See [Source::read].
+    pub fn source_file(&mut self, path : impl AsRef<Path>, parse : impl FnOnce(&str, &mut Source) -> Result<(), String>) -> &mut Self {
+        let path = package_path(path.as_ref());
+        match Source::read(&path, parse) {
+            Ok(Some(source)) => { self.source(source); },
+            Ok(None) if self.watch_missing => println!("cargo:rerun-if-changed={}", path.display()),
+            Ok(None) => (),
+            Err(error) => self.error(error),
+        }
+        self
+    }
+
+    /// Add the TOML file at `path`, relative to the package root, as a source of override values.
+    /// 
+    /// When the environment variable `NSOC_CONFIG` is set, the file it names is added once, at the first
+    /// call, before `path`. It has precedence over all TOML files and must exist. A missing file at `path`
+    /// is ignored like in [ConstFile::source_file]. See [parse_toml](crate::parse_toml) for the supported syntax.
+    #[cfg(feature = "toml")]
+    pub fn toml(&mut self, path : impl AsRef<Path>) -> &mut Self {
+        if !self.config_read {
+            match env_override("NSOC_CONFIG") {
+                Ok(config) => self.config(config),
+                Err(error) => self.error(error),
+            }
+        }
+        self.source_file(path, crate::parse_toml)
+    }
+
+    /// Add the TOML file `config` set by `NSOC_CONFIG`, if any. Later calls of [ConstFile::toml] do not read `NSOC_CONFIG` again.
+    #[cfg(feature = "toml")]
+    fn config(&mut self, config : Option<String>) {
+        self.config_read = true;
+        match config {
+            Some(config) if !package_path(Path::new(&config)).exists() => self.error(NsocError::InvalidSource { 
+                path: package_path(Path::new(&config)), reason: "file set by NSOC_CONFIG does not exist".to_string() }),
+            Some(config) => { self.source_file(config, crate::parse_toml); },
+            None => (),
+        }
+    }
+
+    /// Add the `.env` file at `path`, relative to the package root, as a source of override values.
+    /// 
+    /// A missing file is ignored like in [ConstFile::source_file]. See [parse_dotenv](crate::parse_dotenv) for the supported syntax.
+    #[cfg(feature = "dotenv")]
+    pub fn dotenv(&mut self, path : impl AsRef<Path>) -> &mut Self {
+        self.source_file(path, crate::parse_dotenv)
+    }
+
+    /// Add the JSON file at `path`, relative to the package root, as a source of override values.
+    /// 
+    /// A missing file is ignored like in [ConstFile::source_file]. See [parse_json](crate::parse_json) for the supported syntax.
+    #[cfg(feature = "json")]
+    pub fn json(&mut self, path : impl AsRef<Path>) -> &mut Self {
+        self.source_file(path, crate::parse_json)
+    }
+
+    /// Add the YAML file at `path`, relative to the package root, as a source of override values.
+    /// 
+    /// A missing file is ignored like in [ConstFile::source_file]. See [parse_yaml](crate::parse_yaml) for the supported syntax.
+    #[cfg(feature = "yaml")]
+    pub fn yaml(&mut self, path : impl AsRef<Path>) -> &mut Self {
+        self.source_file(path, crate::parse_yaml)
+    }
+
+    /// Add the RON file at `path`, relative to the package root, as a source of override values.
+    /// 
+    /// A missing file is ignored like in [ConstFile::source_file]. See [parse_ron](crate::parse_ron) for the supported syntax.
+    #[cfg(feature = "ron")]
+    pub fn ron(&mut self, path : impl AsRef<Path>) -> &mut Self {
+        self.source_file(path, crate::parse_ron)
+    }
+
+    /// Value overriding the constant resolved from `env_var`, from the environment variable itself or
+    /// from the first source with the key `env_var`, with or without the prefix of the file, with the
+    /// name of its source.
+    /// 
+    /// Arrays in sources keep their elements, so they are never split by the delimiter of the constant.
+    pub fn lookup(&self, env_var : &str) -> Result<Option<Override>, NsocError> {
+        if let Some(value) = env_override(env_var)? {
+            return Ok(Some(value.into()));
+        }
+
+        let key = env_var.strip_prefix(&self.prefix).unwrap_or(env_var);
+        Ok(self.sources.iter().find_map(|source| source.get(env_var).or_else(|| source.get(key))
+            .map(|value| Override { value: value.clone(), source: Some(source.name().to_string()) })))
+    }
+
+    /// Add a constant overridden by environment variables, named with the prefix of the file unless
+    /// [Const::env] is set, or by the sources of the file.
+    pub fn constant<T : ConstValue>(&mut self, constant : Const<T>) -> &mut Self {
+        let env_var = constant.env_var(&self.prefix);
+        let declaration = constant.declare(&env_var, &mut |env_var| self.lookup(env_var));
+        self.push(declaration);
+        self
+    }
+
+    /// Add a constant of enum type overridden like [ConstFile::constant] by the name of a variant.
+    pub fn variant(&mut self, constant : Const<Variant>) -> &mut Self {
+        let env_var = constant.env_var(&self.prefix);
+        let declaration = constant.declare(&env_var, &mut |env_var| self.lookup(env_var));
+        self.push(declaration);
+        self
+    }
+
+    /// Add a constant of struct type, each field being overridden like [ConstFile::constant] by
+    /// `{ENV_VAR}_{FIELD}`. The errors of all invalid fields are added.
+    pub fn structure(&mut self, constant : ConstStruct) -> &mut Self {
+        let env_var = constant.env_var(&self.prefix);
+        match constant.declare(&env_var, &mut |env_var| self.lookup(env_var)) {
+            Ok(declaration) => self.declare(&declaration),
+            Err(errors) => errors.into_iter().for_each(|error| self.error(error)),
+        }
+        self
+    }
+
+    /// Add a constant overridden by the environment variables returned by `env`, ignoring the sources of the file.
+    pub fn constant_with_env<T : ConstValue>(&mut self, constant : Const<T>, env : &mut Env) -> &mut Self {
+        let env_var = constant.env_var(&self.prefix);
+        self.push(constant.declare(&env_var, env));
+        self
+    }
+
+    /// Name of the file, without extension.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Declarations added so far.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Errors added so far.
+    pub fn errors(&self) -> &[NsocError] {
+        &self.errors
+    }
+
+    /// Add the declaration of a constant, or an error if a constant of the same name was already declared.
+    pub fn declare(&mut self, declaration : &Declaration) {
+        if self.names.contains(&declaration.name) {
+            return self.error(NsocError::InvalidDeclaration { constant: declaration.name.clone(), reason: "a constant of the same name is already declared".to_string() });
+        }
+
+        self.names.push(declaration.name.clone());
+        self.content.push_str(&declaration.to_string());
+    }
+
+    /// Add an error reported when the file is written.
+    pub fn error(&mut self, error : NsocError) {
+        self.errors.push(error);
+    }
+
+    /// Add a declaration or its error.
+    pub fn push(&mut self, declaration : Result<Declaration, NsocError>) {
+        match declaration {
+            Ok(declaration) => self.declare(&declaration),
+            Err(error) => self.error(error),
+        }
+    }
+
+    /// Path of the file in the [out_dir](ConstFile::out_dir) if set, else in the `OUT_DIR`.
+    pub fn path(&self) -> Result<PathBuf, NsocError> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR").map(PathBuf::from).ok_or_else(|| NsocError::Io { path: PathBuf::from(format!("{}.rs", self.name)), 
+                reason: "OUT_DIR is not set, constants must be written by a build script".to_string() })?,
+        };
+
+        Ok(out_dir.join(format!("{}.rs", self.name)))
+    }
+
+    /// Write the file in the `OUT_DIR` and return its path, or all the errors added.
+    /// 
+    /// The content is written in a temporary file renamed over the destination, so a failing build
+    /// script never leaves a half-written file behind.
+    pub fn write(&self) -> Result<PathBuf, Vec<NsocError>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors.clone());
+        }
+
+        let out_path = self.path().map_err(|error| vec![error])?;
+        let tmp_path = out_path.with_extension("rs.tmp");
+        let io_error = |path : &PathBuf| { let path = path.clone(); move |error : std::io::Error| vec![NsocError::Io { path, reason: error.to_string() }] };
+
+        std::fs::write(&tmp_path, &self.content).map_err(io_error(&tmp_path))?;
+        std::fs::rename(&tmp_path, &out_path).map_err(io_error(&out_path))?;
+
+        Ok(out_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConstFile;
+    use crate::{ Const, ConstOptions, Declaration, Modifiers, NsocError, Override, Source, SourceValue, Visibility };
+
+    #[test]
+    fn collect() {
+        let mut file = ConstFile::new("collect");
+        file.push(Declaration::resolve("A", "A", &1u8, &ConstOptions::default(), Modifiers::default(), "", &mut |_| Ok(None)));
+        file.push(Declaration::resolve("B", "B", &1u8, &ConstOptions::default(), Modifiers::default(), "", &mut |_| Ok(Some("x".into()))));
+        file.error(NsocError::InvalidDeclaration { constant: "C".to_string(), reason: "unknown".to_string() });
+        file.push(Declaration::resolve("A", "A", &2u8, &ConstOptions::default(), Modifiers::default(), "", &mut |_| Ok(None)));
+
+        assert_eq!(file.name(), "collect");
+        assert!(file.content().ends_with("pub const A: u8 = 1;\n\n"));
+        assert_eq!(file.errors().len(), 3);
+        assert_eq!(file.errors()[2].to_string(), "Invalid declaration of constant `A`: a constant of the same name is already declared.");
+        assert_eq!(file.write().unwrap_err(), file.errors());
+    }
+
+    #[test]
+    fn prefix() {
+        let mut file = ConstFile::new("prefix");
+        assert_eq!(file.env_var("WIDTH"), "NSOC_WIDTH");
+        assert_eq!(file.prefix("").env_var("WIDTH"), "WIDTH");
+    }
+
+    #[test]
+    fn sources() {
+        let mut config = Source::new("config");
+        config.insert("SOURCES_WIDTH", SourceValue::Value("300".to_string()));
+        config.insert("SOURCES_PORTS", SourceValue::List(vec!["80".to_string(), "443".to_string()]));
+        let mut fallback = Source::new("fallback");
+        fallback.insert("SOURCES_WIDTH", SourceValue::Value("400".to_string()));
+        fallback.insert("SOURCES_HEIGHT", SourceValue::Value("200".to_string()));
+        fallback.insert("APP_SOURCES_DEPTH", SourceValue::Value("3".to_string()));
+        std::env::set_var("APP_SOURCES_HEIGHT", "100");
+
+        let mut file = ConstFile::new("sources");
+        file.prefix("APP_").source(config).source(fallback);
+        assert_eq!(file.sources().len(), 2);
+        assert_eq!(file.sources()[1].name(), "fallback");
+        let found = |value : &str, source : &str| Ok(Some(Override { value: SourceValue::Value(value.to_string()), source: Some(source.to_string()) }));
+        assert_eq!(file.lookup("APP_SOURCES_WIDTH"), found("300", "config"));
+        assert_eq!(file.lookup("APP_SOURCES_HEIGHT"), Ok(Some("100".into())));
+        assert_eq!(file.lookup("APP_SOURCES_PORTS"), Ok(Some(Override { value: SourceValue::List(vec!["80".to_string(), "443".to_string()]), source: Some("config".to_string()) })));
+        assert_eq!(file.lookup("SOURCES_WIDTH"), found("300", "config"));
+        assert_eq!(file.lookup("APP_SOURCES_DEPTH"), found("3", "fallback"));
+        assert_eq!(file.lookup("APP_SOURCES_LENGTH"), Ok(None));
+
+        file.constant(Const::new("SOURCES_PORTS", vec![8080u16]).nodoc().options(ConstOptions::default().delimiter(";")));
+        assert_eq!(file.content(), "pub const SOURCES_PORTS: &'static [u16] = &[80, 443];\n\n");
+    }
+
+    #[test]
+    #[cfg(all(feature = "toml", feature = "dotenv", feature = "json"))]
+    fn source_files() {
+        let dir = std::env::temp_dir().join("nsoc_tests_source_files");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("nsoc.toml"), "[limits]\nmax_conn = 10\n").unwrap();
+        std::fs::write(dir.join("invalid.toml"), "max_conn = ").unwrap();
+
+        std::fs::write(dir.join(".env"), "export LIMITS_MAX_CONN=20\n").unwrap();
+        std::fs::write(dir.join("ci.json"), "{ \"limits\": { \"max_conn\": 30 } }").unwrap();
+
+        let mut file = ConstFile::new("source_files");
+        file.toml(dir.join("nsoc.toml")).toml(dir.join("missing.toml")).toml(dir.join("invalid.toml")).dotenv(dir.join(".env")).json(dir.join("ci.json"));
+        assert_eq!(file.sources().len(), 3);
+        assert_eq!(file.lookup("LIMITS_MAX_CONN").unwrap().map(|found| found.value), Some(SourceValue::Value("10".to_string())));
+        assert_eq!(file.sources()[1].get("LIMITS_MAX_CONN"), Some(&SourceValue::Value("20".to_string())));
+        assert_eq!(file.sources()[2].get("LIMITS_MAX_CONN"), Some(&SourceValue::Value("30".to_string())));
+        assert_eq!(file.sources()[0].get("LIMITS_MAX_CONN"), Some(&SourceValue::Value("10".to_string())));
+        assert_eq!(file.errors(), &[NsocError::InvalidSource { path: dir.join("invalid.toml"), reason: "line 1: expected value".to_string() }]);
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn config() {
+        let dir = std::env::temp_dir().join("nsoc_tests_config");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("config.toml"), "max_conn = 10\n").unwrap();
+        std::fs::write(dir.join("nsoc.toml"), "max_conn = 20\nname = \"nsoc\"\n").unwrap();
+
+        // The config is read once, before every TOML file.
+        let mut file = ConstFile::new("config");
+        file.config(Some(dir.join("config.toml").display().to_string()));
+        file.toml(dir.join("nsoc.toml")).toml(dir.join("nsoc.toml"));
+        assert_eq!(file.sources().iter().map(|source| source.name().to_string()).collect::<Vec<String>>(),
+            vec![dir.join("config.toml").display().to_string(), dir.join("nsoc.toml").display().to_string(), dir.join("nsoc.toml").display().to_string()]);
+        assert_eq!(file.lookup("MAX_CONN").unwrap().map(|found| found.value), Some(SourceValue::Value("10".to_string())));
+        assert_eq!(file.lookup("NAME").unwrap().map(|found| found.value), Some(SourceValue::Value("nsoc".to_string())));
+
+        let mut file = ConstFile::new("config");
+        file.config(Some(dir.join("missing.toml").display().to_string()));
+        assert_eq!(file.errors(), &[NsocError::InvalidSource { path: dir.join("missing.toml"), reason: "file set by NSOC_CONFIG does not exist".to_string() }]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "yaml", feature = "ron"))]
+    fn yaml_and_ron_files() {
+        let dir = std::env::temp_dir().join("nsoc_tests_yaml_and_ron");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("nsoc.yaml"), "limits:\n  max_conn: 10\n").unwrap();
+        std::fs::write(dir.join("nsoc.ron"), "(limits: (max_conn: 20, timeout_ms: 30))").unwrap();
+
+        let mut file = ConstFile::new("yaml_and_ron");
+        file.yaml(dir.join("nsoc.yaml")).ron(dir.join("nsoc.ron"));
+        assert_eq!(file.lookup("LIMITS_MAX_CONN").unwrap().map(|found| found.value), Some(SourceValue::Value("10".to_string())));
+        assert_eq!(file.lookup("LIMITS_TIMEOUT_MS").unwrap().map(|found| found.value), Some(SourceValue::Value("30".to_string())));
+    }
+
+    #[test]
+    fn builder() {
+        let out_dir = std::env::temp_dir().join("nsoc_tests_builder");
+        std::fs::create_dir_all(&out_dir).unwrap();
+
+        let path = ConstFile::new("builder")
+            .out_dir(&out_dir)
+            .prefix("APP_")
+            .constant_with_env(Const::new("WIDTH", 150usize).nodoc().visibility(Visibility::Crate), &mut |_| Ok(None))
+            .constant_with_env(Const::new("NAME", "nsoc").nodoc(), &mut |env_var| Ok((env_var == "APP_NAME").then(|| "builder".into())))
+            .constant_with_env(Const::new("LEVEL", 1u8).nodoc().env("LOG_LEVEL"), &mut |env_var| Ok((env_var == "LOG_LEVEL").then(|| "2".into())))
+            .write()
+            .unwrap();
+
+        assert_eq!(path, out_dir.join("builder.rs"));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "pub(crate) const WIDTH: usize = 150;\n\npub const NAME: &'static str = \"builder\";\n\npub const LEVEL: u8 = 2;\n\n");
+    }
+}
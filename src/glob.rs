@@ -0,0 +1,151 @@
+//! Glob patterns matched by [ConstOptions::pattern](crate::ConstOptions::pattern).
+
+/// Returns true if the whole `value` matches the glob `pattern`.
+/// 
+/// Accepted syntax :
+/// - `*` matches any sequence of characters, including none.
+/// - `?` matches any single character.
+/// - `[abc]`, `[a-z]` match a single character of the class, `[!a-z]` or `[^a-z]` any character not in it.
+/// - `\` escapes the next character.
+/// 
+/// An unclosed `[` is matched literally.
+pub fn glob_match(pattern : &str, value : &str) -> bool {
+    let pattern : Vec<char> = pattern.chars().collect();
+    let value : Vec<char> = value.chars().collect();
+
+    // Position in pattern and value to resume from when the last `*` must match one more character.
+    let mut star : Option<(usize, usize)> = None;
+    let (mut p, mut v) = (0, 0);
+
+    while v < value.len() {
+        if pattern.get(p) == Some(&'*') {
+            star = Some((p + 1, v));
+            p += 1;
+            continue;
+        }
+
+        if let Some(next) = match_char(&pattern, p, value[v]) {
+            p = next;
+            v += 1;
+        } else if let Some((star_p, star_v)) = star {
+            p = star_p;
+            v = star_v + 1;
+            star = Some((star_p, v));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Match character `c` against the token of `pattern` at `p`, returning the position of the next token.
+fn match_char(pattern : &[char], p : usize, c : char) -> Option<usize> {
+    match pattern.get(p)? {
+        '*' => None,
+        '?' => Some(p + 1),
+        '\\' => match pattern.get(p + 1) {
+            Some(escaped) => (*escaped == c).then_some(p + 2),
+            None => (c == '\\').then_some(p + 1),
+        },
+        '[' => match class_end(pattern, p) {
+            Some(end) => class_match(&pattern[p + 1..end], c).then_some(end + 1),
+            None => (c == '[').then_some(p + 1),
+        },
+        literal => (*literal == c).then_some(p + 1),
+    }
+}
+
+/// Position of the `]` closing the class opened at `p`, if any.
+fn class_end(pattern : &[char], p : usize) -> Option<usize> {
+    let mut i = p + 1;
+    if matches!(pattern.get(i), Some('!' | '^')) {
+        i += 1;
+    }
+
+    // A `]` right after the opening is part of the class.
+    if pattern.get(i) == Some(&']') {
+        i += 1;
+    }
+
+    while i < pattern.len() {
+        match pattern[i] {
+            '\\' => i += 2,
+            ']' => return Some(i),
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// Returns true if `c` is in the `class` written between brackets.
+fn class_match(class : &[char], c : char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!' | '^') => (true, &class[1..]),
+        _ => (false, class),
+    };
+
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        let start = if class[i] == '\\' && i + 1 < class.len() { i += 1; class[i] } else { class[i] };
+        if class.get(i + 1) == Some(&'-') && i + 2 < class.len() {
+            let end = if class[i + 2] == '\\' && i + 3 < class.len() { i += 1; class[i + 2] } else { class[i + 2] };
+            found |= (start..=end).contains(&c);
+            i += 3;
+        } else {
+            found |= start == c;
+            i += 1;
+        }
+    }
+
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn literal() {
+        assert!(glob_match("", ""));
+        assert!(glob_match("/api", "/api"));
+        assert!(!glob_match("/api", "/api/"));
+        assert!(!glob_match("/api", "/ap"));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(glob_match("/api/*", "/api/"));
+        assert!(glob_match("/api/*", "/api/v1/users"));
+        assert!(glob_match("*.example.com", "www.example.com"));
+        assert!(!glob_match("*.example.com", "example.com"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+        assert!(glob_match("v?", "v1"));
+        assert!(!glob_match("v?", "v"));
+        assert!(glob_match("**", ""));
+        assert!(glob_match("été?", "étéé"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(glob_match("[a-z]*", "nsoc"));
+        assert!(!glob_match("[a-z]*", "Nsoc"));
+        assert!(glob_match("[!0-9]", "a"));
+        assert!(!glob_match("[^0-9]", "5"));
+        assert!(glob_match("[]a]", "]"));
+        assert!(glob_match("[a-]", "-"));
+        assert!(glob_match("[", "["));
+        assert!(glob_match("v[0-9].[0-9]", "v1.2"));
+    }
+
+    #[test]
+    fn escapes() {
+        assert!(glob_match("\\*", "*"));
+        assert!(!glob_match("\\*", "a"));
+        assert!(glob_match("[\\]]", "]"));
+        assert!(glob_match("a\\", "a\\"));
+    }
+}
@@ -0,0 +1,283 @@
+//! JSON parser reading override values into a [Source].
+//! 
+//! The document must be an object. Nested objects are flattened with `_`, so `{"limits": {"max_conn": 10}}`
+//! sets `LIMITS_MAX_CONN`. Numbers and booleans are kept as written, integer constants also accepting
+//! integral numbers like `1e3` or `1.0`, `null` is an empty value so `Option` constants are `None`, and
+//! arrays of these values override array constants.
+
+use std::ops::{ Deref, DerefMut };
+
+use crate::{ Source, SourceValue };
+use crate::cursor::Cursor;
+
+/// Parse the JSON document `content` into `source`.
+pub fn parse_json(content : &str, source : &mut Source) -> Result<(), String> {
+    let mut parser = Parser(Cursor::new(content));
+    parser.document(source).map_err(|reason| parser.error(reason))
+}
+
+/// Parser moving a [Cursor] over the document.
+struct Parser(Cursor);
+
+impl Deref for Parser {
+    type Target = Cursor;
+
+    fn deref(&self) -> &Cursor {
+        &self.0
+    }
+}
+
+impl DerefMut for Parser {
+    fn deref_mut(&mut self) -> &mut Cursor {
+        &mut self.0
+    }
+}
+
+impl Parser {
+    /// Skip whitespace.
+    fn blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Expect `c` after whitespace.
+    fn expect(&mut self, c : char, context : &str) -> Result<(), String> {
+        self.blank();
+        match self.peek() {
+            Some(found) if found == c => { self.pos += 1; Ok(()) },
+            Some(found) => Err(format!("expected `{}` {}, found `{}`", c, context, found)),
+            None => Err(format!("expected `{}` {}", c, context)),
+        }
+    }
+
+    fn document(&mut self, source : &mut Source) -> Result<(), String> {
+        self.expect('{', "at the start of the document")?;
+        self.object("", source)?;
+
+        self.blank();
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(format!("unexpected `{}` after the document", c)),
+        }
+    }
+
+    /// Parse the members of an object after its opening brace, inserting them with keys prefixed by `key`.
+    fn object(&mut self, key : &str, source : &mut Source) -> Result<(), String> {
+        self.blank();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(());
+        }
+
+        loop {
+            self.expect('"', "before member name")?;
+            let name = self.string()?;
+            let member = if key.is_empty() { name } else { format!("{}_{}", key, name) };
+            self.expect(':', "after member name")?;
+            self.blank();
+
+            match self.peek() {
+                Some('{') => { self.pos += 1; self.object(&member, source)?; },
+                Some('[') => { self.pos += 1; source.insert(&member, SourceValue::List(self.array()?)); },
+                _ => source.insert(&member, SourceValue::Value(self.scalar()?)),
+            }
+
+            self.blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => { self.pos += 1; return Ok(()); },
+                _ => return Err("expected `,` or `}` in object".to_string()),
+            }
+        }
+    }
+
+    /// Parse the elements of an array after its opening bracket.
+    fn array(&mut self) -> Result<Vec<String>, String> {
+        let mut elements = Vec::new();
+
+        self.blank();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(elements);
+        }
+
+        loop {
+            self.blank();
+            if matches!(self.peek(), Some('{' | '[')) {
+                return Err("arrays can only contain strings, numbers, booleans and null".to_string());
+            }
+            elements.push(self.scalar()?);
+
+            self.blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => { self.pos += 1; return Ok(elements); },
+                _ => return Err("expected `,` or `]` in array".to_string()),
+            }
+        }
+    }
+
+    /// Parse a string, number, boolean or null, written like in an environment variable.
+    fn scalar(&mut self) -> Result<String, String> {
+        if self.peek() == Some('"') {
+            self.pos += 1;
+            return self.string();
+        }
+
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+            self.pos += 1;
+        }
+
+        let token : String = self.chars[start..self.pos].iter().collect();
+        match token.as_str() {
+            "true" | "false" => Ok(token),
+            "null" => Ok(String::new()),
+            "" => Err(match self.peek() {
+                Some(c) => format!("expected value, found `{}`", c),
+                None => "expected value".to_string(),
+            }),
+            _ if is_number(&token) => Ok(token),
+            _ => Err(format!("invalid value `{}`", token)),
+        }
+    }
+
+    /// Parse a string after its opening quote.
+    fn string(&mut self) -> Result<String, String> {
+        let mut string = String::new();
+
+        loop {
+            match self.next_char()? {
+                '"' => return Ok(string),
+                '\\' => string.push(match self.next_char()? {
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => self.unicode()?,
+                    c => return Err(format!("invalid escape `\\{}`", c)),
+                }),
+                c if c.is_control() => return Err("control characters must be escaped in strings".to_string()),
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// Next character of a string.
+    fn next_char(&mut self) -> Result<char, String> {
+        let c = self.peek().ok_or_else(|| "unterminated string".to_string())?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    /// Parse a `\u` escape, with the low surrogate of a pair.
+    fn unicode(&mut self) -> Result<char, String> {
+        let high = self.hex()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| format!("invalid unicode escape `{:04x}`", high));
+        }
+
+        if self.next_char()? != '\\' || self.next_char()? != 'u' {
+            return Err(format!("unpaired surrogate `{:04x}`", high));
+        }
+
+        let low = self.hex()?;
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + low.wrapping_sub(0xDC00))
+            .filter(|_| (0xDC00..0xE000).contains(&low))
+            .ok_or_else(|| format!("unpaired surrogate `{:04x}`", high))
+    }
+
+    /// Parse the 4 hexadecimal digits of a `\u` escape.
+    fn hex(&mut self) -> Result<u32, String> {
+        let hex = (0..4).map(|_| self.next_char()).collect::<Result<String, String>>()?;
+        u32::from_str_radix(&hex, 16).map_err(|_| format!("invalid unicode escape `{}`", hex))
+    }
+}
+
+/// Returns true if `token` is a JSON number.
+fn is_number(token : &str) -> bool {
+    let digits = |part : &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let token = token.strip_prefix('-').unwrap_or(token);
+    let (mantissa, exponent) = match token.find(['e', 'E']) {
+        Some(e) => (&token[..e], Some(&token[e + 1..])),
+        None => (token, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    digits(integer) && (integer == "0" || !integer.starts_with('0'))
+        && fraction.map_or(true, digits)
+        && exponent.map_or(true, |exponent| digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ is_number, parse_json };
+    use crate::{ Source, SourceValue };
+
+    #[test]
+    fn values() {
+        let source = Source::parse("overrides.json", r#"{
+            "default_width": 300,
+            "ratio": -1.5e-3,
+            "enabled": false,
+            "cache": null,
+            "name": "nsoc \"quoted\" \u00e9 \ud83d\ude00",
+            "ports": [80, "443", 8080],
+            "empty": [],
+            "limits": { "max_conn": 10, "log": { "level": "debug" } },
+            "none": {}
+        }"#, parse_json).unwrap();
+
+        let get = |key| source.get(key).map(SourceValue::to_string);
+        assert_eq!(get("DEFAULT_WIDTH").as_deref(), Some("300"));
+        assert_eq!(get("RATIO").as_deref(), Some("-1.5e-3"));
+        assert_eq!(get("ENABLED").as_deref(), Some("false"));
+        assert_eq!(get("CACHE").as_deref(), Some(""));
+        assert_eq!(get("NAME").as_deref(), Some("nsoc \"quoted\" é 😀"));
+        assert_eq!(get("PORTS").as_deref(), Some("[80, 443, 8080]"));
+        assert_eq!(get("PORTS_1").as_deref(), Some("443"));
+        assert_eq!(get("EMPTY").as_deref(), Some("[]"));
+        assert_eq!(get("LIMITS_MAX_CONN").as_deref(), Some("10"));
+        assert_eq!(get("LIMITS_LOG_LEVEL").as_deref(), Some("debug"));
+        assert!(Source::parse("overrides.json", "{}", parse_json).unwrap().keys().next().is_none());
+    }
+
+    #[test]
+    fn numbers() {
+        for number in ["0", "-0", "12", "1.5", "1e9", "1E+2", "-0.5e-7"] {
+            assert!(is_number(number), "{}", number);
+        }
+
+        for number in ["", "-", "01", "1.", ".5", "1e", "+1", "0x10", "NaN"] {
+            assert!(!is_number(number), "{}", number);
+        }
+    }
+
+    #[test]
+    fn integral_numbers() {
+        let source = Source::parse("overrides.json", r#"{ "width": 1e3, "name": 1.0, "ports": [80.0, 4.43e2] }"#, parse_json).unwrap();
+        assert_eq!(source.get("WIDTH").unwrap().parse::<u32>(","), Ok(1000));
+        assert_eq!(source.get("NAME").unwrap().parse::<String>(","), Ok("1.0".to_string()));
+        assert_eq!(source.get("PORTS").unwrap().parse::<Vec<u16>>(","), Ok(vec![80, 443]));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Source::parse("overrides.json", "[1]", parse_json).unwrap_err(), "line 1: expected `{` at the start of the document, found `[`");
+        assert_eq!(Source::parse("overrides.json", "{\n\"a\": 1,\n\"b\" 2}", parse_json).unwrap_err(), "line 3: expected `:` after member name, found `2`");
+        assert_eq!(Source::parse("overrides.json", "{\"a\": [[1]]}", parse_json).unwrap_err(), "line 1: arrays can only contain strings, numbers, booleans and null");
+        assert_eq!(Source::parse("overrides.json", "{\"a\": 01}", parse_json).unwrap_err(), "line 1: invalid value `01`");
+        assert_eq!(Source::parse("overrides.json", "{\"a\": \"open", parse_json).unwrap_err(), "line 1: unterminated string");
+        assert_eq!(Source::parse("overrides.json", "{\"a\": 1} x", parse_json).unwrap_err(), "line 1: unexpected `x` after the document");
+        assert_eq!(Source::parse("overrides.json", "{\"a\": 1,}", parse_json).unwrap_err(), "line 1: expected `\"` before member name, found `}`");
+        assert_eq!(Source::parse("overrides.json", "{\"a\": \"\\ud83d\"}", parse_json).unwrap_err(), "line 1: unpaired surrogate `d83d`");
+    }
+}
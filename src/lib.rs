@@ -1,5 +1,7 @@
 #![doc(html_logo_url = "https://avatars.githubusercontent.com/u/67743099?v=4")]
 #![doc(html_favicon_url = "https://avatars.githubusercontent.com/u/67743099?v=4")]
+// NsocError is only created when the build fails, its size does not matter.
+#![allow(clippy::result_large_err)]
 
 /* 
 Copyright (c) 2024  NickelAnge.Studio 
@@ -27,54 +29,157 @@ SOFTWARE.
 
 //! Nifty and Simple Overridable Constant provides neat macros to create constants that can be overriden compilation. 
 //! 
+//! Constants are declared in `build.rs` with [write_file!] and [write_const!], or without macros with
+//! [ConstFile], [Const] and [ConstStruct], then included in the crate with [load_const!]. Constants can also be declared
+//! in `[package.metadata.nsoc]` of `Cargo.toml`, or shared by a workspace in `[workspace.metadata.nsoc]`,
+//! and generated with a single call to `generate()` (with the `toml` feature).
+//! 
 //! It is based on [Lukas Kalbertodt](https://stackoverflow.com/users/2408867/lukas-kalbertodt) answer on [How can I override a constant via a compiler option?](https://stackoverflow.com/questions/37526598/how-can-i-override-a-constant-via-a-compiler-option/37526735#37526735).
 //! 
 
+mod modifier;
+pub use modifier::{ Modifiers, Visibility };
+
+mod doc;
+pub use doc::{ const_doc, custom_doc };
+
+mod value;
+pub use value::{ ConstValue, DEFAULT_DELIMITER };
+
+mod condition;
+pub use condition::Condition;
+
+mod options;
+pub use options::{ Check, ConstOptions, Integer };
+
+mod glob;
+pub use glob::glob_match;
+
+mod error;
+pub use error::{ NsocError, report };
+
+mod env;
+pub use env::{ env_override, Env, Override };
+
+mod variant;
+pub use variant::Variant;
+
+mod declaration;
+pub use declaration::Declaration;
+
+mod constant;
+pub use constant::{ Const, ConstStruct };
+
+#[cfg(any(feature="toml", feature="json", feature="ron"))]
+mod cursor;
+
+mod source;
+pub use source::{ Source, SourceValue };
+
+#[cfg(feature = "toml")]
+mod toml;
+#[cfg(feature = "toml")]
+pub use toml::parse_toml;
+
+#[cfg(feature = "dotenv")]
+mod dotenv;
+#[cfg(feature = "dotenv")]
+pub use dotenv::parse_dotenv;
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::parse_json;
+
+#[cfg(feature = "yaml")]
+mod yaml;
+#[cfg(feature = "yaml")]
+pub use yaml::parse_yaml;
+
+#[cfg(feature = "ron")]
+mod ron;
+#[cfg(feature = "ron")]
+pub use ron::parse_ron;
+
+mod file;
+pub use file::ConstFile;
+
+#[cfg(feature = "toml")]
+mod metadata;
+#[cfg(feature = "toml")]
+pub use metadata::{ generate, manifest_metadata, workspace_manifest };
+
 #[macro_export]
 /// Used in `build.rs` to quickly write constants in a file generated in the `OUT_DIR`.
 /// 
+/// Every [write_const!] inside `$code` appends its declaration to `$filehandle`, a [ConstFile]. The
+/// file is only written once `$code` is done, through a temporary file renamed over the destination,
+/// so a failing build script never leaves a half-written file behind.
+/// 
+/// Invalid constants do not stop the build right away. All errors of `$code` are [reported](report)
+/// together once it is done, failing the build.
+/// 
 /// # Usage
-/// create_const_file! { {$filename,} $filehandle, $code}
+/// write_file! { {$filename,} $filehandle, $code}
 /// 
 /// - `$filename` *`Optional`* 
-///     - Name of the file that will be created in the `OUT_DIR`. 
-///     - The file `.rs` will be added to the end if not specified. 
+///     - Name of the file that will be created in the `OUT_DIR`, without extension. 
+///     - The extension `.rs` is always added to the end so [load_const!] can find it with the same name. 
 ///     - If not supplied, default filename is `{CARGO_PKG_NAME}_nsdcs.rs`.
 /// - `$filehandle` 
-///     - Variable used as file handle. 
+///     - Variable used as file handle, a [ConstFile]. 
 ///     - Must be provided to comply to [Rust macro hygiene](https://danielkeep.github.io/tlborm/book/mbe-min-hygiene.html).
 /// - `$code` 
 ///     - Section where you write the [write_const!] macro.
+///     - Methods of `$filehandle` called before, like [ConstFile::prefix], `ConstFile::toml`, `ConstFile::dotenv` or `ConstFile::json`, apply to the constants written after.
 /// 
 /// # Example
 /// In `build.rs` main
-/// ```
-/// fn main() {
-///     write_file!{ f,
-///         write_const!(f, DEFAULT_WIDTH, usize, 150, "Default frame width");
-///         write_const!(f, DEFAULT_HEIGHT, usize, 50, "Default frame height")
-///     }
+/// ```no_run
+/// use nsoc::{write_file, write_const};
+/// 
+/// write_file!{ f,
+///     write_const!(f, DEFAULT_WIDTH, usize, 150, "Default frame width");
+///     write_const!(f, DEFAULT_HEIGHT, usize, 50, "Default frame height")
 /// }
 /// ```
 /// 
+#[cfg_attr(feature="toml", doc = r#"
+With values overridden by the file at `NSOC_CONFIG` if set, then by `nsoc.toml` in the package root.
+```no_run
+use nsoc::{write_file, write_const};
+
+write_file!{ "config", f,
+    f.toml("nsoc.toml");
+    write_const!(f, MAX_ENTRY, u32, 100, "Maximum entry count")
+}
+```
+"#)]
+/// 
 macro_rules! write_file {
 
+    // With filename supplied
+    ($filename : literal, $filehandle : ident, $($code : tt)*) => {
+        $crate::write_file!(@path std::string::String::from($filename), $filehandle, $($code)*)
+    };
+
     // Without filename supplied. Create default filename {CARGO_PKG_NAME}_nsdcs.rs.
-    ($filehandle : expr, $code : block) => {
-        create_const_file!(format!("{}_nsdcs.rs", env!("CARGO_PKG_NAME")), $filehandle, $code);
+    ($filehandle : ident, $($code : tt)*) => {
+        $crate::write_file!(@path std::format!("{}_nsdcs", std::env::var("CARGO_PKG_NAME").expect("CARGO_PKG_NAME is not set!")), $filehandle, $($code)*)
     };
-    
-    // With filename supplied
-    ($filename : literal, $filehandle : expr, $code : block) => {
-        // File destination
-        let out_path = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap()).join(format!("{}.rs", $filename));
 
-        // Create file
-        let mut f = std::fs::File::create(&out_path).expect("Could not create file {}!", $filename);
+    // Common arm which is not used directly.
+    (@path $filename : expr, $filehandle : ident, $($code : tt)*) => {{
+        #[allow(unused_mut)]
+        let mut $filehandle = $crate::ConstFile::new(&$filename);
 
         // Insert code block
-        $code
-    };
+        $($code)*;
+
+        if let Err(errors) = $filehandle.write() {
+            $crate::report(&errors);
+        }
+    }};
 }
 
 #[macro_export]
@@ -82,48 +187,162 @@ macro_rules! write_file {
 /// 
 /// All contants have `pub` [visibility](https://doc.rust-lang.org/reference/visibility-and-privacy.html) unless specified via modifier.
 /// 
+/// Unless `nodoc` or `cc` are given, documentation is generated with [const_doc] using `$comment` as description.
+/// 
+/// The constant is overridden by the environment variable `{prefix}{$const_name}`, read with [env_override],
+/// where the prefix is the package name in upper case followed by `_` unless changed with [ConstFile::prefix].
+/// The `env` option overrides it with another variable. Without environment variable, the value is looked up
+/// in the [sources](Source) of `$filehandle` before using `$default`.
+/// 
+/// Tuples are overridden field by field with `{ENV_VAR}_{index}`. `Option` constants are `None` when
+/// overridden with an empty value or `none`. The fields of an `Option` of a tuple are overridden only
+/// when its default is `Some`, see [ConstValue].
+/// 
 /// # Usage
-/// write_const! { {$modifiers,} $filehandle, $const_name, $const_type, $default {,$comment} }
+/// write_const! { {$modifiers,} $filehandle, $const_name, $const_type, $default {,$comment} {, $option = $value}* }
+/// 
+/// write_const! { {$modifiers,} $filehandle, $const_name, struct $struct_type { $($field : $field_type = $field_default),* } {,$comment} {, $option = $value}* }
+/// 
+/// write_const! { {$modifiers,} $filehandle, $const_name, enum $enum_type { $($variant),* } = $default {,$comment} {, $option = $value}* }
 /// 
-/// - `$modifiers` *`Optional`* Literal used to modify certain parameters.  
+/// - `$modifiers` *`Optional`* Literal used to modify certain parameters, separated by spaces or commas. See [Modifiers].  
 ///     - `nodoc` : No documentation will be generated for this constants.
 ///     - `cc` : Overwrite the default comment with a custom one.
 ///     - `priv` : Make contant visibility private.
 ///     - `pcrate` : Make contant visibility `pub (crate)`. 
 ///     - `pself` : Make contant visibility `pub (self)`. 
 ///     - `psuper` : Make contant visibility `pub (super)`. 
-/// - `$filehandle` Filehandle specified in [create_const_file] macro.
+/// - `$filehandle` Filehandle specified in [write_file!] macro, a [ConstFile] collecting declarations and errors.
 /// - `$const_name` Name of the contant. Should be formatted as `SCREAMING_SNAKE_CASE` as specified in the [naming guideline](https://rust-lang.github.io/api-guidelines/naming.html).
+/// - `$const_type` Type of the constant, implementing [ConstValue]. `String` constants are written as `&'static str`.
+/// - `$default` Value of the constant when not overridden.
+/// - `struct $struct_type { .. }` Struct literal constant of type `$struct_type`, which must be accessible where the file is loaded.
+///     - Each field `$field` of type `$field_type` is overridden by `{ENV_VAR}_{$field}` in upper case, or is `$field_default`.
+/// - `enum $enum_type { .. } = $default` Enum constant of type `$enum_type`, which must be accessible where the file is loaded.
+///     - Overridden by the name of one of the `$variant`, or is `$enum_type::$default`. See [Variant].
+/// - `$comment` *`Optional`* Description of the constant, or whole documentation with `cc`.
+/// - `$option = $value` *`Optional`* Options of the constant. See [ConstOptions]. Only `env` and `delimiter` apply to
+///   struct constants, and only `env` and `case_sensitive` to enum constants.
+///     - `env = "FRAME_WIDTH"` : Environment variable overriding the constant instead of `{prefix}{$const_name}`.
+///     - `delimiter = ";"` : Delimiter between array and slice elements in environment variables, which cannot be empty. Default is `,`.
+///     - `case_sensitive = true` : Enum variants must be given with the exact case in environment variables. Default is `false`.
+///     - `range = 1..=64` : Value must be within range. Inclusive, exclusive and half-open ranges are accepted.
+///     - `power_of_two = true` : Integer value must be a power of two.
+///     - `multiple_of = 64` : Integer value must be a multiple of the given value.
+///     - `validator = not_reserved` : Custom validator `fn(&T) -> Result<(), String>` run after parsing. The reason returned
+///       follows the value in error messages, i.e. `must not be a reserved port`.
+///     - `pattern = "/api/*"` : String value must match the glob pattern, see [glob_match] for the syntax.
+///     - `min_len = 1`, `max_len = 64` : String value must be at least or at most this many characters long.
+///     - `profile = ("release", 10_000)` : Default value when building with the `dev` or `release` profile, or with `opt-level=N`
+///       or `debug=V`, see [Condition::Profile]. Other profile names are invalid. Can be given more than once.
+///     - `target = ("pointer_width=16", 256)` : Default value when building for this target, matched with `arch=A`, `os=O`,
+///       `pointer_width=N` or a glob pattern of the target triple, see [Condition::Target]. Can be given more than once.
+///       The first matching `profile` or `target` default is used.
 /// 
+/// # Errors
+/// An [NsocError] is added to `$filehandle` when `$modifiers` contains an unknown modifier or more than one
+/// visibility modifier, when an option does not apply to the constant, when an environment variable cannot be
+/// parsed or is not an accepted enum variant, or when the default or overridden value does not respect its
+/// constraints.
+/// [write_file!] then fails the build.
 /// 
-
+/// # Example
+/// In `build.rs` main
+/// ```no_run
+/// use nsoc::{write_file, write_const};
+/// 
+/// write_file!{ f,
+///     write_const!(f, APP_NAME, &str, "nsoc", "Application name");
+///     write_const!("pcrate", f, ALLOWED_PORTS, [u16; 3], [80, 443, 8080], "Allowed ports");
+///     write_const!(f, LOG_TARGETS, Vec<&str>, vec!["stdout"], "Log targets", delimiter = ";");
+///     write_const!(f, DEFAULT_SIZE, (usize, usize), (80, 50), "Default size overridden by {PREFIX}DEFAULT_SIZE_0 and {PREFIX}DEFAULT_SIZE_1");
+///     write_const!(f, MAX_CACHE_SIZE, Option<usize>, None, "Maximum cache size, unbounded if None");
+///     write_const!(f, API_PATH, &str, "/api/v1", "API base path", pattern = "/api/*", max_len = 32);
+///     write_const!(f, WORKER_THREADS, usize, 4, "Worker threads", range = 1..=64);
+///     write_const!(f, LOG_FILTER, &str, "info", "Log filter overridden by RUST_LOG", env = "RUST_LOG");
+///     write_const!(f, BUFFER_SIZE, usize, 4096, "Buffer size", power_of_two = true, multiple_of = 64);
+///     write_const!(f, LOG_BUFFER, usize, 10, "Log buffer size", profile = ("release", 10_000));
+///     write_const!(f, RX_BUFFER, usize, 4096, "Receive buffer size", target = ("pointer_width=16", 256), target = ("thumbv7em-*", 1024));
+///     write_const!(f, PORT, u16, 8080, "Server port",
+///         validator = |port : &u16| if *port < 1024 { Err("must not be a reserved port".to_string()) } else { Ok(()) });
+///     write_const!(f, LIMITS, struct crate::Limits { max_conn: u32 = 100, timeout_ms: u64 = 5000 }, "Overridden by {PREFIX}LIMITS_MAX_CONN and {PREFIX}LIMITS_TIMEOUT_MS");
+///     write_const!(f, LOG_LEVEL, enum crate::Level { Trace, Debug, Info, Warn, Error } = Info, "Log level")
+/// }
+/// ```
 macro_rules! write_const {
+    // Enum call with no comments and no documentation
+    ($filehandle : expr, $const_name : expr, enum $enum_type : path { $($variants : tt)* } = $default : ident $(, $option : ident = $value : expr)*) => {
+        $crate::write_const!("nodoc", $filehandle, $const_name, enum $enum_type { $($variants)* } = $default, "" $(, $option = $value)*)
+    };
+
+    // Enum call with no modifiers
+    ($filehandle : expr, $const_name : expr, enum $enum_type : path { $($variants : tt)* } = $default : ident, $comment : literal $(, $option : ident = $value : expr)*) => {
+        $crate::write_const!("", $filehandle, $const_name, enum $enum_type { $($variants)* } = $default, $comment $(, $option = $value)*)
+    };
+
+    // Full enum call which is usually not used directly.
+    ($modifiers : literal, $filehandle : expr, $const_name : expr, enum $enum_type : path { $($variant : ident),* $(,)? } = $default : ident, $comment : literal $(, $option : ident = $value : expr)*) => {{
+        let constant = $crate::Variant::new(std::stringify!($enum_type), &[$(std::stringify!($variant)),*], std::stringify!($default))
+            .map_err(|reason| $crate::NsocError::InvalidDeclaration { constant: std::stringify!($const_name).to_string(), reason })
+            .map(|default_value| $crate::Const::variant(std::stringify!($const_name), default_value)
+                .doc($comment)
+                .options($crate::ConstOptions::default()$(.$option($value))*));
+
+        match $crate::write_const!(@modifiers $modifiers, $const_name).and_then(|modifiers| constant.map(|constant| constant.modifiers(modifiers))) {
+            Ok(constant) => { $filehandle.variant(constant); },
+            Err(error) => $filehandle.error(error),
+        }
+    }};
+
+    // Struct literal call with no comments and no documentation
+    ($filehandle : expr, $const_name : expr, struct $struct_type : path { $($fields : tt)* } $(, $option : ident = $value : expr)*) => {
+        $crate::write_const!("nodoc", $filehandle, $const_name, struct $struct_type { $($fields)* }, "" $(, $option = $value)*)
+    };
+
+    // Struct literal call with no modifiers
+    ($filehandle : expr, $const_name : expr, struct $struct_type : path { $($fields : tt)* }, $comment : literal $(, $option : ident = $value : expr)*) => {
+        $crate::write_const!("", $filehandle, $const_name, struct $struct_type { $($fields)* }, $comment $(, $option = $value)*)
+    };
+
+    // Full struct literal call which is usually not used directly.
+    ($modifiers : literal, $filehandle : expr, $const_name : expr, struct $struct_type : path { $($field : ident : $field_type : ty = $field_default : expr),* $(,)? }, $comment : literal $(, $option : ident = $value : expr)*) => {{
+        let constant = $crate::ConstStruct::new(std::stringify!($const_name), std::stringify!($struct_type))
+            $(.field(std::stringify!($field), { let default_value : $field_type = $field_default; default_value }))*
+            .doc($comment)
+            .options($crate::ConstOptions::default()$(.$option($value))*);
+
+        match $crate::write_const!(@modifiers $modifiers, $const_name) {
+            Ok(modifiers) => { $filehandle.structure(constant.modifiers(modifiers)); },
+            Err(error) => $filehandle.error(error),
+        }
+    }};
+
     // Call with no comments and no documentation
-    ($filehandle : expr, $const_name : expr, $const_type : ty, $default : expr) => {
-        write_const!("nodoc", $filehandle, $const_name, $const_type, $default, "")
+    ($filehandle : expr, $const_name : expr, $const_type : ty, $default : expr $(, $option : ident = $value : expr)*) => {
+        $crate::write_const!("nodoc", $filehandle, $const_name, $const_type, $default, "" $(, $option = $value)*)
     };
 
     // Call with no modifiers
-    ($filehandle : expr, $const_name : expr, $const_type : ty, $default : expr, $comment : literal) => {
-        write_const!("", $filehandle, $const_name, $const_type, $default, $comment)
+    ($filehandle : expr, $const_name : expr, $const_type : ty, $default : expr, $comment : literal $(, $option : ident = $value : expr)*) => {
+        $crate::write_const!("", $filehandle, $const_name, $const_type, $default, $comment $(, $option = $value)*)
     };
 
     // Full call which is usually not used directly.
-    ($modifiers : literal, $filehandle : expr, $const_name : expr, $const_type : ty, $default : expr, $comment : literal) => {
-        let const_value =  match option_env!(std::stringify!($const_name)){  // Try to get cargo argument specified
-            Some(env_var) => {
-                match env_var.parse::<$const_type>() {    // Parse cargo argument variable according to type.
-                    Ok(value) => value, // Return value if valid
-                    Err(err) => panic!("{}", err),  // Panic if invalid
-                }
-            },
-            None => $default,   // Returns default value if not supplied in cargo arguments
-        };
-    
-        println!("{}\n pub const {}: {} = {};\n", $comment, std::stringify!($const_name), std::stringify!($const_type), const_value);
-        //std::write!(&mut $file, "{}\n {} const {}: {} = {};\n", $comment, $visibility, std::stringify!($const_name), std::stringify!($const_type), const_value)
-        //    .expect("Could not write file `nslog_contants.rs`");
-        println!("cargo:rerun-if-env-changed={}", std::stringify!($const_name));
+    ($modifiers : literal, $filehandle : expr, $const_name : expr, $const_type : ty, $default : expr, $comment : literal $(, $option : ident = $value : expr)*) => {{
+        let constant = $crate::Const::<$const_type>::new(std::stringify!($const_name), $default)
+            .doc($comment)
+            .options($crate::ConstOptions::default()$(.$option($value))*);
+
+        match $crate::write_const!(@modifiers $modifiers, $const_name) {
+            Ok(modifiers) => { $filehandle.constant(constant.modifiers(modifiers)); },
+            Err(error) => $filehandle.error(error),
+        }
+    }};
+
+    // Parse modifiers, which is not used directly.
+    (@modifiers $modifiers : literal, $const_name : expr) => {
+        $modifiers.parse::<$crate::Modifiers>()
+            .map_err(|reason| $crate::NsocError::InvalidDeclaration { constant: std::stringify!($const_name).to_string(), reason })
     };
 
 }
@@ -131,60 +350,277 @@ macro_rules! write_const {
 
 #[macro_export]
 /// Add dynamic constants into your code.
+/// 
+/// Include the file generated by [write_file!] in `build.rs`. The file name follow the same rule
+/// as [write_file!] so the same `$filename` must be given to both macros.
+/// 
+/// # Usage
+/// load_const! { {$filename} {,} {$visibility mod $module} }
+/// 
+/// - `$filename` *`Optional`* 
+///     - Name of the file given to [write_file!], without extension. 
+///     - If not supplied, default filename is `{CARGO_PKG_NAME}_nsdcs.rs`.
+/// - `$visibility mod $module` *`Optional`* 
+///     - Wrap the constants in a module instead of adding them where the macro is called.
+/// 
+/// # Example
+/// Constants written with `write_file!{ f, ... }` added to the crate root.
+/// ```ignore
+/// nsoc::load_const!();
+/// ```
+/// 
+/// Constants written with `write_file!{ "config", f, ... }` accessible via `config::DEFAULT_WIDTH`.
+/// ```ignore
+/// nsoc::load_const!("config", pub mod config);
+/// ```
 macro_rules! load_const {
+    // Default filename {CARGO_PKG_NAME}_nsdcs.rs.
     () => {
+        include!(concat!(env!("OUT_DIR"), "/", env!("CARGO_PKG_NAME"), "_nsdcs.rs"));
+    };
 
+    // Default filename wrapped in a module.
+    ($vis : vis mod $module : ident) => {
+        $vis mod $module {
+            $crate::load_const!();
+        }
     };
+
+    // With filename supplied
     ($filename : literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $filename, ".rs"));
+    };
 
-    }
+    // With filename supplied wrapped in a module.
+    ($filename : literal, $vis : vis mod $module : ident) => {
+        $vis mod $module {
+            $crate::load_const!($filename);
+        }
+    };
 }
 
 
 #[cfg(test)]
 mod tests {
-    use super::write_const;
+    /// Point `OUT_DIR` to the temporary directory like cargo does for build scripts.
+    fn set_out_dir() -> std::path::PathBuf {
+        let out_dir = std::env::temp_dir().join("nsoc_tests");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        std::env::set_var("OUT_DIR", &out_dir);
+        out_dir
+    }
 
     #[test]
     fn it_works() {
-        write_const!(f, DEFAULT_WIDTH, usize, 50);
+        set_out_dir();
+        write_file!("it_works", f, write_const!(f, DEFAULT_WIDTH, usize, 50));
     }
-}
-/*
-#[macro_export]
-macro_rules! write_const {
 
-}
+    #[test]
+    fn write_file_emits_constants() {
+        let out_dir = set_out_dir();
+        write_file!{ "write_file_emits_constants", f,
+            write_const!(f, DEFAULT_WIDTH, usize, 150, "Default frame width");
+            write_const!(f, DEFAULT_HEIGHT, usize, 50, "Default frame height")
+        }
 
-#[macro_export]
-macro_rules! write_const_no_comment {
+        let content = std::fs::read_to_string(out_dir.join("write_file_emits_constants.rs")).unwrap();
+        assert_eq!(content, std::format!("{}pub const DEFAULT_WIDTH: usize = 150;\n\n{}pub const DEFAULT_HEIGHT: usize = 50;\n\n",
+            crate::const_doc("Default frame width", "150", "usize", &[("NSOC_DEFAULT_WIDTH".to_string(), "150".to_string())]),
+            crate::const_doc("Default frame height", "50", "usize", &[("NSOC_DEFAULT_HEIGHT".to_string(), "50".to_string())])));
+        assert!(!out_dir.join("write_file_emits_constants.rs.tmp").exists());
+    }
 
-}
+    #[test]
+    fn write_file_default_filename() {
+        let out_dir = set_out_dir();
+        write_file!(f, write_const!(f, DEFAULT_DEPTH, u8, 3));
 
+        let content = std::fs::read_to_string(out_dir.join("nsoc_nsdcs.rs")).unwrap();
+        assert_eq!(content, "pub const DEFAULT_DEPTH: u8 = 3;\n\n");
+    }
 
- write_env_var_to_file!( "NSLOG_ENTRY_COUNT", f, DEFAULT_ENTRY_COUNT, "
-    /// Maximum count of entry the log can contain at once.
-    ///
-    /// # Default
-    /// 50
-    ///
-    /// The default value can be overwritten when compiling or running cargo.
-    /// 
-    /// # Example
-    /// This will set the log count to 100.
-    /// ```
-    /// NSLOG_ENTRY_COUNT=100 cargo run
-    /// ```
-    /// ");
+    #[test]
+    fn write_const_modifiers() {
+        let out_dir = set_out_dir();
+        write_file!{ "write_const_modifiers", f,
+            write_const!("priv cc", f, PRIVATE, u8, 1, "Private");
+            write_const!("pcrate,cc", f, CRATE, u8, 2, "Crate");
+            write_const!("cc pself", f, SELF, u8, 3, "Self");
+            write_const!("psuper", f, SUPER, u8, 4, "Super");
+            write_const!("nodoc pcrate", f, NODOC_CRATE, u8, 5, "Not written")
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let content = std::fs::read_to_string(out_dir.join("write_const_modifiers.rs")).unwrap();
+        assert_eq!(content, std::format!("/// Private\nconst PRIVATE: u8 = 1;\n\n\
+            /// Crate\npub(crate) const CRATE: u8 = 2;\n\n\
+            /// Self\npub(self) const SELF: u8 = 3;\n\n\
+            {}pub(super) const SUPER: u8 = 4;\n\n\
+            pub(crate) const NODOC_CRATE: u8 = 5;\n\n", crate::const_doc("Super", "4", "u8", &[("NSOC_SUPER".to_string(), "4".to_string())])));
+    }
 
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn write_const_prefix() {
+        let out_dir = set_out_dir();
+        std::env::set_var("APP_PREFIXED_WIDTH", "300");
+        std::env::set_var("APP_PREFIXED_LIMITS_MAX_CONN", "10");
+        std::env::set_var("PREFIXED_LEVEL", "Debug");
+        write_file!{ "write_const_prefix", f,
+            f.prefix("APP_");
+            write_const!(f, PREFIXED_WIDTH, usize, 150, "Width");
+            write_const!(f, PREFIXED_LIMITS, struct Limits { max_conn: u32 = 100 });
+            write_const!(f, PREFIXED_MODE, enum Level { Debug, Info } = Info, env = "PREFIXED_LEVEL")
+        }
+
+        let content = std::fs::read_to_string(out_dir.join("write_const_prefix.rs")).unwrap();
+        assert!(content.contains("/// APP_PREFIXED_WIDTH=150 cargo build\n"));
+        assert!(content.contains("pub const PREFIXED_WIDTH: usize = 300;\n\n"));
+        assert!(content.contains("pub const PREFIXED_LIMITS: Limits = Limits { max_conn: 10 };\n\n"));
+        assert!(content.ends_with("pub const PREFIXED_MODE: Level = Level::Debug;\n\n"));
+    }
+
+    #[test]
+    fn write_const_string() {
+        let out_dir = set_out_dir();
+        write_file!{ "write_const_string", f,
+            write_const!("nodoc", f, APP_NAME, &str, "nsoc", "");
+            write_const!("nodoc", f, LOG_PREFIX, String, String::from("[\"log\"] "), "")
+        }
+
+        let content = std::fs::read_to_string(out_dir.join("write_const_string.rs")).unwrap();
+        assert_eq!(content, "pub const APP_NAME: &'static str = \"nsoc\";\n\npub const LOG_PREFIX: &'static str = r#\"[\"log\"] \"#;\n\n");
+    }
+
+    #[test]
+    fn write_const_list() {
+        let out_dir = set_out_dir();
+        write_file!{ "write_const_list", f,
+            write_const!(f, ALLOWED_PORTS, [u16; 3], [80, 443, 8080]);
+            write_const!(f, LOG_TARGETS, Vec<&str>, vec!["stdout", "file"], delimiter = ";");
+            write_const!(f, LEVELS, &[char], &['a', 'b'], "Levels", delimiter = ";")
+        }
+
+        let content = std::fs::read_to_string(out_dir.join("write_const_list.rs")).unwrap();
+        assert!(content.starts_with("pub const ALLOWED_PORTS: [u16; 3] = [80, 443, 8080];\n\n\
+            pub const LOG_TARGETS: &'static [&'static str] = &[\"stdout\", \"file\"];\n\n"));
+        assert!(content.contains("/// NSOC_LEVELS='a;b' cargo build\n"));
+        assert!(content.ends_with("pub const LEVELS: &'static [char] = &['a', 'b'];\n\n"));
+    }
+
+    #[test]
+    fn write_const_composite() {
+        let out_dir = set_out_dir();
+        std::env::set_var("NSOC_COMPOSITE_SIZE_1", "25");
+        std::env::set_var("NSOC_COMPOSITE_LIMITS_TIMEOUT_MS", "10");
+        write_file!{ "write_const_composite", f,
+            write_const!(f, COMPOSITE_SIZE, (usize, usize), (80, 50));
+            write_const!("pcrate", f, COMPOSITE_LIMITS, struct crate::Limits { max_conn: u32 = 100, timeout_ms: u64 = 5000, }, "Limits");
+            write_const!(f, COMPOSITE_UNIT, struct Unit {})
+        }
+
+        let content = std::fs::read_to_string(out_dir.join("write_const_composite.rs")).unwrap();
+        assert!(content.starts_with("pub const COMPOSITE_SIZE: (usize, usize) = (80, 25);\n\n"));
+        assert!(content.contains("/// `crate::Limits { max_conn: 100, timeout_ms: 5000 }`\n"));
+        assert!(content.contains("/// NSOC_COMPOSITE_LIMITS_MAX_CONN=100 NSOC_COMPOSITE_LIMITS_TIMEOUT_MS=5000 cargo build\n"));
+        assert!(content.contains("pub(crate) const COMPOSITE_LIMITS: crate::Limits = crate::Limits { max_conn: 100, timeout_ms: 10 };\n\n"));
+        assert!(content.ends_with("pub const COMPOSITE_UNIT: Unit = Unit {};\n\n"));
+    }
+
+    #[test]
+    fn write_const_enum() {
+        let out_dir = set_out_dir();
+        std::env::set_var("NSOC_ENUM_LOG_LEVEL", "debug");
+        std::env::set_var("NSOC_ENUM_MODE", "Slow");
+        write_file!{ "write_const_enum", f,
+            write_const!(f, ENUM_LOG_LEVEL, enum crate::Level { Trace, Debug, Info, } = Info);
+            write_const!("pcrate", f, ENUM_MODE, enum Mode { Fast, Slow } = Fast, "Mode", case_sensitive = true)
+        }
+
+        let content = std::fs::read_to_string(out_dir.join("write_const_enum.rs")).unwrap();
+        assert!(content.starts_with("pub const ENUM_LOG_LEVEL: crate::Level = crate::Level::Debug;\n\n"));
+        assert!(content.contains("/// Mode\n///\n/// Accepted values are `Fast`, `Slow`.\n"));
+        assert!(content.contains("/// `Mode::Fast`\n"));
+        assert!(content.ends_with("pub(crate) const ENUM_MODE: Mode = Mode::Slow;\n\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value `verbose` of environment variable `NSOC_ENUM_UNKNOWN` for constant `ENUM_UNKNOWN`: unknown variant `verbose` of `Level`, accepted variants are Debug, Info.")]
+    fn write_const_enum_unknown() {
+        set_out_dir();
+        std::env::set_var("NSOC_ENUM_UNKNOWN", "verbose");
+        write_file!("write_const_enum_unknown", f, write_const!(f, ENUM_UNKNOWN, enum Level { Debug, Info } = Info));
+    }
+
+    #[test]
+    #[should_panic(expected = "3 error(s) while generating constants:\n  \
+        - Invalid value `wide` of environment variable `NSOC_ERRORS_WIDTH` for constant `ERRORS_WIDTH`: invalid digit found in string. Expected `usize`, default is `150`.\n  \
+        - Invalid declaration of constant `ERRORS_HEIGHT`: unknown modifier `nope`, accepted modifiers are nodoc, cc, priv, pcrate, pself, psuper.\n  \
+        - Invalid value `x` of environment variable `NSOC_ERRORS_LIMITS_MAX_CONN` for constant `ERRORS_LIMITS.max_conn`")]
+    fn write_file_reports_all_errors() {
+        let out_dir = set_out_dir();
+        std::env::set_var("NSOC_ERRORS_WIDTH", "wide");
+        std::env::set_var("NSOC_ERRORS_LIMITS_MAX_CONN", "x");
+        let _ = std::fs::remove_file(out_dir.join("write_file_reports_all_errors.rs"));
+
+        let result = std::panic::catch_unwind(|| write_file!{ "write_file_reports_all_errors", f,
+            write_const!(f, ERRORS_WIDTH, usize, 150);
+            write_const!("nope", f, ERRORS_HEIGHT, usize, 50, "");
+            write_const!(f, ERRORS_DEPTH, usize, 1);
+            write_const!(f, ERRORS_LIMITS, struct Limits { max_conn: u32 = 100 })
+        });
+
+        // Nothing is written when constants are invalid.
+        assert!(!out_dir.join("write_file_reports_all_errors.rs").exists());
+        std::panic::resume_unwind(result.unwrap_err());
+    }
+
+    #[test]
+    fn write_const_validated() {
+        let out_dir = set_out_dir();
+        std::env::set_var("NSOC_VALIDATED_THREADS", "16");
+        write_file!{ "write_const_validated", f,
+            write_const!(f, VALIDATED_THREADS, usize, 4, "Threads", range = 1..=64, power_of_two = true);
+            write_const!(f, VALIDATED_RATIO, f32, 0.5, "", range = 0.0..1.0)
+        }
+
+        let content = std::fs::read_to_string(out_dir.join("write_const_validated.rs")).unwrap();
+        assert!(content.starts_with("/// Threads\n///\n/// The value must be :\n/// - >= 1 and <= 64\n/// - a power of two\n///\n"));
+        assert!(content.contains("pub const VALIDATED_THREADS: usize = 16;\n\n"));
+        assert!(content.contains("/// The value must be :\n/// - >= 0.0 and < 1.0\n///\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value `100` of environment variable `NSOC_INVALID_THREADS` for constant `INVALID_THREADS`: must be >= 1 and <= 64. Expected `usize`, default is `4`.")]
+    fn write_const_out_of_range() {
+        set_out_dir();
+        std::env::set_var("NSOC_INVALID_THREADS", "100");
+        write_file!("write_const_out_of_range", f, write_const!(f, INVALID_THREADS, usize, 4, "Threads", range = 1..=64));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value `22` of environment variable `NSOC_VALIDATED_PORT` for constant `VALIDATED_PORT`: must not be a reserved port. Expected `u16`, default is `8080`.")]
+    fn write_const_validator() {
+        fn not_reserved(port : &u16) -> Result<(), String> {
+            if *port < 1024 { Err("must not be a reserved port".to_string()) } else { Ok(()) }
+        }
+
+        set_out_dir();
+        std::env::set_var("NSOC_VALIDATED_PORT", "22");
+        write_file!("write_const_validator", f, write_const!(f, VALIDATED_PORT, u16, 8080, "Port", validator = not_reserved));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value `/v1` of environment variable `NSOC_VALIDATED_PATH` for constant `VALIDATED_PATH`: must match pattern `/api/*`. Expected `&'static str`, default is `\"/api/v1\"`.")]
+    fn write_const_pattern() {
+        set_out_dir();
+        std::env::set_var("NSOC_VALIDATED_PATH", "/v1");
+        write_file!("write_const_pattern", f, write_const!(f, VALIDATED_PATH, &str, "/api/v1", "Path", pattern = "/api/*", min_len = 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid declaration of constant `UNKNOWN`: unknown modifier `public`, accepted modifiers are")]
+    fn write_const_unknown_modifier() {
+        set_out_dir();
+        write_file!("write_const_unknown_modifier", f, write_const!("public", f, UNKNOWN, u8, 1, "Unknown"));
     }
 }
-*/
\ No newline at end of file
@@ -0,0 +1,367 @@
+//! Constants declared in the package manifest, under `[package.metadata.nsoc]`, or shared by the
+//! workspace under `[workspace.metadata.nsoc]`.
+
+use std::ops::Bound;
+use std::path::{ Path, PathBuf };
+
+use crate::{ glob_match, Const, ConstFile, ConstOptions, ConstValue, Modifiers, NsocError, Source, SourceValue, DEFAULT_DELIMITER };
+use crate::source::package_path;
+
+/// Generate the constants declared in `[package.metadata.nsoc]` of the package manifest and in
+/// `[workspace.metadata.nsoc]` of the workspace manifest, in the file [write_file!](crate::write_file)
+/// would write, so `build.rs` only has to call this function.
+/// 
+/// The file is included with [load_const!](crate::load_const) like any other.
+/// 
+/// # Example
+/// ```toml
+/// [package.metadata.nsoc]
+/// file = "config"     # Optional, default is {CARGO_PKG_NAME}_nsdcs
+/// prefix = "APP_"     # Optional, default is the package name in upper case followed by `_`
+/// 
+/// [[package.metadata.nsoc.constants]]
+/// name = "DEFAULT_WIDTH"
+/// type = "usize"
+/// default = 150
+/// doc = "Default frame width"
+/// range = "1..=4096"  # Optional
+/// modifiers = "pcrate" # Optional, see Modifiers
+/// env = "FRAME_WIDTH" # Optional, see ConstOptions::env
+/// ```
+/// 
+/// Constants of the workspace are declared the same way, see [ConstFile::workspace_metadata].
+/// 
+/// # Panics
+/// [Reports](crate::report) the errors and fails the build if a manifest or a declaration is invalid.
+pub fn generate() {
+    let manifest = package_path(Path::new("Cargo.toml"));
+    println!("cargo:rerun-if-changed={}", manifest.display());
+
+    let metadata = std::fs::read_to_string(&manifest).map_err(|error| error.to_string())
+        .and_then(|content| manifest_metadata(&content, "package.metadata.nsoc"))
+        .unwrap_or_else(|reason| crate::report(&[NsocError::InvalidSource { path: manifest.clone(), reason }]));
+
+    let name = metadata.get("FILE").map(SourceValue::to_string)
+        .unwrap_or_else(|| format!("{}_nsdcs", std::env::var("CARGO_PKG_NAME").expect("CARGO_PKG_NAME is not set!")));
+
+    let mut file = ConstFile::new(&name);
+    if let Some(prefix) = metadata.get("PREFIX").map(SourceValue::to_string) {
+        file.prefix(&prefix);
+    }
+
+    if let Err(errors) = file.workspace_metadata().metadata(&metadata).write() {
+        crate::report(&errors);
+    }
+}
+
+/// Manifest of the workspace of the package, found like cargo does from `CARGO_MANIFEST_DIR` : the
+/// `Cargo.toml` of the directory set by `package.workspace`, else the first `Cargo.toml` with a `[workspace]`
+/// table in `CARGO_MANIFEST_DIR` or its ancestors whose `members` contain the package and whose `exclude`
+/// does not. A member listed without glob pattern is not excluded.
+/// 
+/// Packages which are members only as path dependencies of other members are not found.
+pub fn workspace_manifest() -> Option<PathBuf> {
+    find_workspace(&package_path(Path::new("")))
+}
+
+/// [Workspace manifest](workspace_manifest) of the package in `dir`.
+fn find_workspace(dir : &Path) -> Option<PathBuf> {
+    let read = |manifest : &Path, table : &str| {
+        let content = std::fs::read_to_string(manifest).ok()?;
+        let mut source = Source::new(table);
+        crate::toml::parse_toml_table(&content, table, &mut source).ok()?.then_some(source)
+    };
+
+    if let Some(workspace) = read(&dir.join("Cargo.toml"), "package.workspace").and_then(|package| package.get("").map(SourceValue::to_string)) {
+        return Some(dir.join(workspace).join("Cargo.toml"));
+    }
+
+    dir.ancestors().find_map(|root| {
+        let manifest = root.join("Cargo.toml");
+        let workspace = read(&manifest, "workspace")?;
+        let paths = |key| match workspace.get(key) {
+            Some(SourceValue::List(paths)) => paths.iter().map(|path| components(path)).collect(),
+            _ => Vec::new(),
+        };
+
+        let package = components(&dir.strip_prefix(root).ok()?.to_string_lossy());
+        let members : Vec<Vec<String>> = paths("MEMBERS");
+        let member = members.iter().any(|member| member.len() == package.len() && member.iter().zip(&package).all(|(pattern, name)| glob_match(pattern, name)));
+        let excluded = !members.contains(&package) && paths("EXCLUDE").iter().any(|exclude| package.starts_with(exclude));
+        (package.is_empty() || (member && !excluded)).then_some(manifest)
+    })
+}
+
+/// Components of a relative path of the workspace, i.e. `crates/*`.
+fn components(path : &str) -> Vec<String> {
+    path.split(['/', '\\']).filter(|component| !component.is_empty() && *component != ".").map(str::to_string).collect()
+}
+
+/// Read the TOML table `table` of the manifest `content` and its sub-tables, i.e. `package.metadata.nsoc`.
+/// 
+/// Keys are relative to `table`. The rest of the manifest may use values the parser does not support, i.e.
+/// dates, see [parse_toml](crate::parse_toml) for the syntax supported inside `table`.
+pub fn manifest_metadata(content : &str, table : &str) -> Result<Source, String> {
+    let source = Source::parse(table, content, |content, source| crate::toml::parse_toml_table(content, table, source).map(|_| ()))?;
+    match source.get("") {
+        Some(_) => Err(format!("`{}` must be a table", table)),
+        None => Ok(source),
+    }
+}
+
+impl ConstFile {
+    /// Add the constants declared in the array `constants` of `metadata`, as read by [manifest_metadata].
+    /// 
+    /// Supported types are primitives, `&str` and `String`, slices of these types written `&[T]` or
+    /// `Vec<T>`, and `Option<T>`.
+    pub fn metadata(&mut self, metadata : &Source) -> &mut Self {
+        let mut indexes : Vec<usize> = metadata.keys()
+            .filter_map(|key| key.strip_prefix("CONSTANTS_")?.split('_').next()?.parse().ok())
+            .collect();
+        indexes.sort();
+        indexes.dedup();
+
+        for index in indexes {
+            let field = |name : &str| metadata.get(&format!("CONSTANTS_{}_{}", index, name)).map(SourceValue::to_string);
+            let constant = field("NAME").unwrap_or_else(|| format!("constants[{}]", index));
+            let invalid = |reason : String| NsocError::InvalidDeclaration { constant: constant.clone(), reason };
+
+            let definition = match (field("NAME"), field("TYPE"), metadata.get(&format!("CONSTANTS_{}_DEFAULT", index))) {
+                (None, _, _) => Err(invalid("missing `name`".to_string())),
+                (_, None, _) => Err(invalid("missing `type`".to_string())),
+                (_, _, None) => Err(invalid("missing `default`".to_string())),
+                (Some(name), Some(const_type), Some(default)) => field("MODIFIERS").unwrap_or_default().parse::<Modifiers>().map_err(invalid)
+                    .map(|modifiers| Definition { name, const_type, default: default.clone(), modifiers, doc: field("DOC").unwrap_or_default(), range: field("RANGE"), env: field("ENV") }),
+            };
+
+            match definition.and_then(|definition| self.definition(&definition).map_err(invalid)) {
+                Ok(()) => (),
+                Err(error) => self.error(error),
+            }
+        }
+
+        self
+    }
+
+    /// Add the constants declared in `[workspace.metadata.nsoc]` of the [workspace manifest](workspace_manifest),
+    /// so every package of the workspace generates the same constants.
+    /// 
+    /// ```toml
+    /// [workspace.metadata.nsoc]
+    /// prefix = "NET_"     # Optional, default is no prefix
+    /// 
+    /// [[workspace.metadata.nsoc.constants]]
+    /// name = "MAX_PACKET_SIZE"
+    /// type = "usize"
+    /// default = 1500
+    /// ```
+    /// 
+    /// The environment variables overriding these constants use the prefix of the workspace instead of
+    /// the prefix of the file, i.e. `NET_MAX_PACKET_SIZE`, so setting one variable overrides the constant
+    /// in every package. Nothing is added if the package is not part of a workspace, and a constant also
+    /// declared by the package is reported as an [NsocError::InvalidDeclaration].
+    pub fn workspace_metadata(&mut self) -> &mut Self {
+        let Some(manifest) = workspace_manifest() else {
+            return self;
+        };
+        println!("cargo:rerun-if-changed={}", manifest.display());
+
+        match std::fs::read_to_string(&manifest).map_err(|error| error.to_string()).and_then(|content| manifest_metadata(&content, "workspace.metadata.nsoc")) {
+            Ok(metadata) => {
+                let prefix = self.env_var("");
+                self.prefix(&metadata.get("PREFIX").map(SourceValue::to_string).unwrap_or_default()).metadata(&metadata).prefix(&prefix);
+            },
+            Err(reason) => self.error(NsocError::InvalidSource { path: manifest, reason }),
+        }
+
+        self
+    }
+
+    /// Add the constant of `definition`, calling [Definition::declare] with its type.
+    fn definition(&mut self, definition : &Definition) -> Result<(), String> {
+        /// Match the type names of `$const_type`, with its slices and options.
+        macro_rules! types {
+            ($($name : literal => $const_type : ty),*) => {
+                match definition.const_type.replace(' ', "").as_str() {
+                    $(
+                        $name => definition.declare::<$const_type>(self),
+                        concat!("&[", $name, "]") | concat!("&'static[", $name, "]") | concat!("Vec<", $name, ">") => definition.declare::<Vec<$const_type>>(self),
+                        concat!("Option<", $name, ">") => definition.declare::<Option<$const_type>>(self),
+                    )*
+                    const_type => Err(format!("unsupported type `{}`", const_type)),
+                }
+            };
+        }
+
+        types!("u8" => u8, "u16" => u16, "u32" => u32, "u64" => u64, "u128" => u128, "usize" => usize,
+            "i8" => i8, "i16" => i16, "i32" => i32, "i64" => i64, "i128" => i128, "isize" => isize,
+            "f32" => f32, "f64" => f64, "bool" => bool, "char" => char,
+            "&str" => &'static str, "&'staticstr" => &'static str, "String" => String)
+    }
+}
+
+/// Declaration of a constant read from the metadata.
+struct Definition {
+    name : String,
+    const_type : String,
+    default : SourceValue,
+    modifiers : Modifiers,
+    doc : String,
+    range : Option<String>,
+    env : Option<String>,
+}
+
+impl Definition {
+    /// Add the constant to `file` with its value of type `T`.
+    fn declare<T : ConstValue + PartialOrd + 'static>(&self, file : &mut ConstFile) -> Result<(), String> {
+        let default = self.default.parse::<T>(DEFAULT_DELIMITER).map_err(|reason| format!("invalid default `{}`: {}", self.default, reason))?;
+
+        let mut options = ConstOptions::default();
+        if let Some(range) = &self.range {
+            options = options.range(parse_range::<T>(range)?);
+        }
+        if let Some(env) = &self.env {
+            options = options.env(env);
+        }
+
+        file.constant(Const::new(&self.name, default).doc(&self.doc).modifiers(self.modifiers).options(options));
+        Ok(())
+    }
+}
+
+/// Bounds of a range written like in Rust, i.e. `1..=64`, `1..64`, `1..` or `..=64`.
+fn parse_range<T : ConstValue>(range : &str) -> Result<(Bound<T>, Bound<T>), String> {
+    let (start, end) = range.split_once("..").ok_or_else(|| format!("invalid range `{}`, expected `min..max` or `min..=max`", range))?;
+    let bound = |value : &str| T::parse_env(value.trim()).map_err(|reason| format!("invalid bound `{}` of range `{}`: {}", value.trim(), range, reason));
+
+    let start = match start.trim() {
+        "" => Bound::Unbounded,
+        start => Bound::Included(bound(start)?),
+    };
+    let end = match end.strip_prefix('=') {
+        Some(end) => Bound::Included(bound(end)?),
+        None if end.trim().is_empty() => Bound::Unbounded,
+        None => Bound::Excluded(bound(end)?),
+    };
+
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::{ find_workspace, manifest_metadata, parse_range };
+    use crate::{ ConstFile, NsocError, SourceValue };
+
+    const MANIFEST : &str = r#"
+[package]
+name = "nslog"
+description = """Multi-line strings
+are parsed."""
+released = 1979-05-27
+values = [
+  [1, 2],
+]
+
+[[package.metadata.nsoc.constants]]
+name = "DEFAULT_WIDTH"
+type = "usize"
+default = 150
+doc = "Default frame width"
+range = "1..=4096"
+
+[[package.metadata.nsoc.constants]]
+name = "LOG_TARGETS"
+type = "&[&str]"
+default = ["stdout", "file, rotated"]
+modifiers = "pcrate nodoc"
+
+[[package.metadata.nsoc.constants]]
+name = "CACHE_SIZE"
+type = "Option<u32>"
+default = ""
+env = "CACHE"
+
+[package.metadata.nsoc]
+prefix = "APP_"
+
+[[bin]]
+name = "nslog"
+"#;
+
+    #[test]
+    fn metadata() {
+        let metadata = manifest_metadata(MANIFEST, "package.metadata.nsoc").unwrap();
+        assert_eq!(metadata.get("PREFIX").map(SourceValue::to_string).as_deref(), Some("APP_"));
+        assert_eq!(metadata.get("CONSTANTS_1_DEFAULT"), Some(&SourceValue::List(vec!["stdout".to_string(), "file, rotated".to_string()])));
+        assert_eq!(metadata.get("CONSTANTS_1_NAME").map(SourceValue::to_string).as_deref(), Some("LOG_TARGETS"));
+        assert_eq!(metadata.get("CONSTANTS_0_NAME").map(SourceValue::to_string).as_deref(), Some("DEFAULT_WIDTH"));
+        assert!(metadata.get("NAME").is_none());
+        assert!(metadata.get("CONSTANTS_2_PREFIX").is_none());
+        assert_eq!(manifest_metadata("[package.metadata]\nnsoc = 1\n", "package.metadata.nsoc").unwrap_err(), "`package.metadata.nsoc` must be a table");
+
+        let mut file = ConstFile::new("metadata");
+        file.prefix("METADATA_").metadata(&metadata);
+        assert_eq!(file.errors(), &[]);
+        assert!(file.content().contains("/// Default frame width\n///\n/// The value must be :\n/// - >= 1 and <= 4096\n"));
+        assert!(file.content().contains("pub const DEFAULT_WIDTH: usize = 150;\n\n"));
+        assert!(file.content().contains("pub(crate) const LOG_TARGETS: &'static [&'static str] = &[\"stdout\", \"file, rotated\"];\n\n"));
+        assert!(file.content().contains("/// CACHE=none cargo build\n"));
+        assert!(file.content().ends_with("pub const CACHE_SIZE: Option<u32> = None;\n\n"));
+    }
+
+    #[test]
+    fn invalid_definitions() {
+        let metadata = manifest_metadata("[[package.metadata.nsoc.constants]]\nname = \"A\"\ntype = \"u8\"\ndefault = 300\n\
+            [[package.metadata.nsoc.constants]]\ntype = \"u8\"\n\
+            [[package.metadata.nsoc.constants]]\nname = \"C\"\ntype = \"Foo\"\ndefault = 1\n\
+            [[package.metadata.nsoc.constants]]\nname = \"D\"\ntype = \"u8\"\ndefault = 1\nmodifiers = \"public\"\n", "package.metadata.nsoc").unwrap();
+
+        let mut file = ConstFile::new("invalid_definitions");
+        file.metadata(&metadata);
+        assert_eq!(file.errors().iter().map(NsocError::to_string).collect::<Vec<String>>(), vec![
+            "Invalid declaration of constant `A`: invalid default `300`: number too large to fit in target type.",
+            "Invalid declaration of constant `constants[1]`: missing `name`.",
+            "Invalid declaration of constant `C`: unsupported type `Foo`.",
+            "Invalid declaration of constant `D`: unknown modifier `public`, accepted modifiers are nodoc, cc, priv, pcrate, pself, psuper.",
+        ]);
+    }
+
+    #[test]
+    fn workspace() {
+        let dir = std::env::temp_dir().join("nsoc_tests_workspace");
+        let member = dir.join("crates").join("net");
+        std::fs::create_dir_all(&member).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        std::fs::write(dir.join("crates").join("Cargo.toml"), "[package]\nname = \"crates\"\n").unwrap();
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"net\"\n").unwrap();
+
+        assert_eq!(find_workspace(&member), Some(dir.join("Cargo.toml")));
+        assert_eq!(find_workspace(&dir), Some(dir.join("Cargo.toml")));
+        assert_eq!(find_workspace(&dir.join("crates")), None);
+
+        std::fs::write(dir.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/net\"]\n").unwrap();
+        assert_eq!(find_workspace(&member), None);
+
+        std::fs::write(dir.join("Cargo.toml"), "[workspace]\nmembers = [\"./crates/net/\"]\nexclude = [\"crates\"]\n").unwrap();
+        assert_eq!(find_workspace(&member), Some(dir.join("Cargo.toml")));
+
+        std::fs::write(dir.join("Cargo.toml"), "[workspace.metadata.nsoc]\nprefix = \"NET_\"\n").unwrap();
+        assert_eq!(find_workspace(&member), None);
+        assert_eq!(find_workspace(&dir), Some(dir.join("Cargo.toml")));
+
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"net\"\nworkspace = \"../..\"\n").unwrap();
+        assert_eq!(find_workspace(&member), Some(member.join("../..").join("Cargo.toml")));
+    }
+
+    #[test]
+    fn range() {
+        assert_eq!(parse_range::<u8>("1..=64"), Ok((Bound::Included(1), Bound::Included(64))));
+        assert_eq!(parse_range::<u8>(" 1 .. 64 "), Ok((Bound::Included(1), Bound::Excluded(64))));
+        assert_eq!(parse_range::<f32>("0.5.."), Ok((Bound::Included(0.5), Bound::Unbounded)));
+        assert_eq!(parse_range::<i8>("..=-1"), Ok((Bound::Unbounded, Bound::Included(-1))));
+        assert_eq!(parse_range::<u8>("64"), Err("invalid range `64`, expected `min..max` or `min..=max`".to_string()));
+        assert_eq!(parse_range::<u8>("a..b"), Err("invalid bound `a` of range `a..b`: invalid digit found in string".to_string()));
+    }
+}
@@ -0,0 +1,136 @@
+//! Modifiers accepted by [write_const!](crate::write_const).
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Modifiers names accepted in the `$modifiers` literal.
+pub const MODIFIERS: [&str; 6] = ["nodoc", "cc", "priv", "pcrate", "pself", "psuper"];
+
+/// [Visibility](https://doc.rust-lang.org/reference/visibility-and-privacy.html) of a generated constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    /// `pub`
+    #[default]
+    Public,
+
+    /// No visibility keyword.
+    Private,
+
+    /// `pub(crate)`
+    Crate,
+
+    /// `pub(self)`
+    SelfModule,
+
+    /// `pub(super)`
+    Super,
+}
+
+impl Display for Visibility {
+    /// Write the visibility keyword followed by a space, or nothing if private.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "pub "),
+            Visibility::Private => Ok(()),
+            Visibility::Crate => write!(f, "pub(crate) "),
+            Visibility::SelfModule => write!(f, "pub(self) "),
+            Visibility::Super => write!(f, "pub(super) "),
+        }
+    }
+}
+
+/// Modifiers parsed from the `$modifiers` literal of [write_const!](crate::write_const).
+/// 
+/// Modifiers are separated by spaces or commas, i.e. `"nodoc pcrate"` or `"cc,priv"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modifiers {
+    /// Documentation is generated for the constant. Removed by `nodoc`.
+    pub doc: bool,
+
+    /// Comment replace the generated documentation. Set by `cc`.
+    pub custom_comment: bool,
+
+    /// Visibility of the constant. Set by `priv`, `pcrate`, `pself` and `psuper`.
+    pub visibility: Visibility,
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Self { doc: true, custom_comment: false, visibility: Visibility::Public }
+    }
+}
+
+impl FromStr for Modifiers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::default();
+        let mut visibility : Option<&str> = None;
+
+        for modifier in s.split(|c: char| c == ',' || c.is_whitespace()).filter(|m| !m.is_empty()) {
+            let vis = match modifier {
+                "nodoc" => { modifiers.doc = false; continue },
+                "cc" => { modifiers.custom_comment = true; continue },
+                "priv" => Visibility::Private,
+                "pcrate" => Visibility::Crate,
+                "pself" => Visibility::SelfModule,
+                "psuper" => Visibility::Super,
+                _ => return Err(format!("unknown modifier `{}`, accepted modifiers are {}", modifier, MODIFIERS.join(", "))),
+            };
+
+            // Only one visibility can be given.
+            if let Some(previous) = visibility {
+                if previous != modifier {
+                    return Err(format!("modifiers `{}` and `{}` are both visibility modifiers", previous, modifier));
+                }
+            }
+            visibility = Some(modifier);
+            modifiers.visibility = vis;
+        }
+
+        Ok(modifiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Modifiers, Visibility};
+
+    #[test]
+    fn empty() {
+        assert_eq!("".parse::<Modifiers>(), Ok(Modifiers::default()));
+        assert_eq!(" , ".parse::<Modifiers>(), Ok(Modifiers::default()));
+    }
+
+    #[test]
+    fn visibility() {
+        for (modifier, vis, keyword) in [
+            ("priv", Visibility::Private, ""),
+            ("pcrate", Visibility::Crate, "pub(crate) "),
+            ("pself", Visibility::SelfModule, "pub(self) "),
+            ("psuper", Visibility::Super, "pub(super) "),
+        ] {
+            let modifiers = modifier.parse::<Modifiers>().unwrap();
+            assert_eq!(modifiers.visibility, vis);
+            assert_eq!(modifiers.visibility.to_string(), keyword);
+            assert!(modifiers.doc);
+            assert!(!modifiers.custom_comment);
+        }
+        assert_eq!(Visibility::Public.to_string(), "pub ");
+    }
+
+    #[test]
+    fn combination() {
+        assert_eq!("nodoc pcrate".parse::<Modifiers>(), Ok(Modifiers { doc: false, custom_comment: false, visibility: Visibility::Crate }));
+        assert_eq!("cc,psuper".parse::<Modifiers>(), Ok(Modifiers { doc: true, custom_comment: true, visibility: Visibility::Super }));
+        assert_eq!("nodoc, cc".parse::<Modifiers>(), Ok(Modifiers { doc: false, custom_comment: true, visibility: Visibility::Public }));
+        assert_eq!("priv priv".parse::<Modifiers>().unwrap().visibility, Visibility::Private);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!("nodoc pub".parse::<Modifiers>(), Err("unknown modifier `pub`, accepted modifiers are nodoc, cc, priv, pcrate, pself, psuper".to_string()));
+        assert!("NODOC".parse::<Modifiers>().is_err());
+        assert_eq!("priv pcrate".parse::<Modifiers>(), Err("modifiers `priv` and `pcrate` are both visibility modifiers".to_string()));
+    }
+}
@@ -0,0 +1,338 @@
+//! Options given to [write_const!](crate::write_const) as `key = value` after the comment.
+
+use std::fmt::Debug;
+use std::ops::{ Bound, RangeBounds };
+use std::rc::Rc;
+
+use crate::{ glob_match, Condition, ConstValue };
+use crate::value::DEFAULT_DELIMITER;
+
+/// Check of a constant value, returning why the value is invalid.
+pub type Check<T> = Rc<dyn Fn(&T) -> Result<(), String>>;
+
+/// Options of a constant written by [write_const!](crate::write_const).
+/// 
+/// Each `key = value` option given to [write_const!](crate::write_const) calls the method with
+/// the same name, i.e. `delimiter = ";"` calls [ConstOptions::delimiter].
+#[derive(Clone)]
+pub struct ConstOptions<T> {
+    /// Delimiter between elements of arrays and slices in environment variables.
+    pub delimiter: String,
+
+    /// Enum variants must be given with the exact case in environment variables.
+    pub case_sensitive: bool,
+
+    /// Checks the default and overridden values must pass.
+    pub checks: Vec<Check<T>>,
+
+    /// Description of the constraints checked, added to the documentation.
+    pub constraints: Vec<String>,
+
+    /// Environment variable overriding the constant, instead of the prefixed name of the constant.
+    pub env: Option<String>,
+
+    /// Default values used instead of the default of the constant when their condition matches the build.
+    pub defaults: Vec<(Condition, T)>,
+}
+
+impl<T> Default for ConstOptions<T> {
+    fn default() -> Self {
+        Self { delimiter: DEFAULT_DELIMITER.to_string(), case_sensitive: false, checks: Vec::new(), constraints: Vec::new(), env: None, defaults: Vec::new() }
+    }
+}
+
+impl<T> Debug for ConstOptions<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConstOptions")
+            .field("delimiter", &self.delimiter)
+            .field("case_sensitive", &self.case_sensitive)
+            .field("checks", &self.checks.len())
+            .field("constraints", &self.constraints)
+            .field("env", &self.env)
+            .field("defaults", &self.defaults.iter().map(|(condition, _)| condition).collect::<Vec<&Condition>>())
+            .finish()
+    }
+}
+
+impl<T> ConstOptions<T> {
+    /// Set the delimiter between elements of arrays and slices. Default is `,`.
+    /// 
+    /// The declaration of a constant with an empty delimiter is invalid.
+    pub fn delimiter(mut self, delimiter : &str) -> Self {
+        self.delimiter = delimiter.to_string();
+        self
+    }
+
+    /// Set if enum variants must be given with the exact case. Default is `false`.
+    pub fn case_sensitive(mut self, case_sensitive : bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Override the constant with the environment variable `env` instead of the prefixed name of the constant.
+    pub fn env(mut self, env : &str) -> Self {
+        self.env = Some(env.to_string());
+        self
+    }
+
+    /// Use `default` instead of the default of the constant when building with `profile`, i.e.
+    /// `profile = ("release", 10_000)`. See [Condition::Profile] for the accepted profiles.
+    /// 
+    /// The first matching default is used, an environment variable or a source still overrides it.
+    pub fn profile(mut self, (profile, default) : (&str, T)) -> Self {
+        self.defaults.push((Condition::Profile(profile.to_string()), default));
+        self
+    }
+
+    /// Use `default` instead of the default of the constant when building for `target`, i.e.
+    /// `target = ("pointer_width=16", 256)` or `target = ("thumbv7em-*", 1024)`. See [Condition::Target]
+    /// for the accepted targets.
+    /// 
+    /// The first matching default is used, an environment variable or a source still overrides it.
+    pub fn target(mut self, (target, default) : (&str, T)) -> Self {
+        self.defaults.push((Condition::Target(target.to_string()), default));
+        self
+    }
+
+    /// Default value of the current build with its condition, the first of [ConstOptions::defaults]
+    /// matching the build or `default` without condition.
+    pub fn default_value<'a>(&'a self, default : &'a T) -> (&'a T, Option<&'a Condition>) {
+        self.defaults.iter().find(|(condition, _)| condition.matches())
+            .map_or((default, None), |(condition, default)| (default, Some(condition)))
+    }
+
+    /// Add a constraint the value must respect, where `description` completes "The value must be".
+    pub fn constraint(self, description : &str, check : impl Fn(&T) -> bool + 'static) -> Self {
+        self.described_check(description, &format!("must be {}", description), check)
+    }
+
+    /// Add a check failing with `reason`, documented by `description`.
+    fn described_check(mut self, description : &str, reason : &str, check : impl Fn(&T) -> bool + 'static) -> Self {
+        let reason = reason.to_string();
+        self.checks.push(Rc::new(move |value| if check(value) { Ok(()) } else { Err(reason.clone()) }));
+        self.constraints.push(description.to_string());
+        self
+    }
+
+    /// Add a custom validator, run on the default and overridden values after parsing.
+    /// 
+    /// The reason returned on failure follows the value in error messages, i.e. `must not be a reserved port`.
+    pub fn validator(mut self, validator : impl Fn(&T) -> Result<(), String> + 'static) -> Self {
+        self.checks.push(Rc::new(validator));
+        self
+    }
+
+    /// Check `value` with each check, returning the reason of the first that fails.
+    pub fn validate(&self, value : &T) -> Result<(), String> {
+        self.checks.iter().try_for_each(|check| check(value))
+    }
+
+    /// Check that only the options `supported` are set, besides `env`, for the constants which cannot use
+    /// the others, i.e. `&["case_sensitive"]` for an enum constant.
+    pub(crate) fn supported(&self, supported : &[&str]) -> Result<(), String> {
+        let used = [
+            ("delimiter", self.delimiter != DEFAULT_DELIMITER),
+            ("case_sensitive", self.case_sensitive),
+            ("validator", !self.checks.is_empty() || !self.constraints.is_empty()),
+            ("profile", self.defaults.iter().any(|(condition, _)| matches!(condition, Condition::Profile(_)))),
+            ("target", self.defaults.iter().any(|(condition, _)| matches!(condition, Condition::Target(_)))),
+        ];
+
+        match used.iter().find(|(option, used)| *used && !supported.contains(option)) {
+            Some(("validator", _)) => Err(format!("constraints and validators do not apply, only the `env` and `{}` options do", supported.join("`, `"))),
+            Some((option, _)) => Err(format!("option `{}` does not apply, only the `env` and `{}` options do", option, supported.join("`, `"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T : ConstValue + PartialOrd + 'static> ConstOptions<T> {
+    /// Value must be within `range`, i.e. `1..=64`, `1..` or `..64`.
+    pub fn range<R : RangeBounds<T>>(self, range : R) -> Self {
+        let start = range.start_bound().cloned();
+        let end = range.end_bound().cloned();
+
+        let bounds : Vec<String> = [
+            match &start { Bound::Included(min) => Some(format!(">= {}", min.to_env())), Bound::Excluded(min) => Some(format!("> {}", min.to_env())), Bound::Unbounded => None },
+            match &end { Bound::Included(max) => Some(format!("<= {}", max.to_env())), Bound::Excluded(max) => Some(format!("< {}", max.to_env())), Bound::Unbounded => None },
+        ].into_iter().flatten().collect();
+
+        if bounds.is_empty() {
+            return self;
+        }
+
+        self.constraint(&bounds.join(" and "), move |value| (start.as_ref(), end.as_ref()).contains(value))
+    }
+}
+
+impl<T : AsRef<str> + 'static> ConstOptions<T> {
+    /// String value must match the glob `pattern`, see [glob_match] for the syntax.
+    pub fn pattern(self, pattern : &str) -> Self {
+        let description = format!("matching pattern `{}`", pattern);
+        let reason = format!("must match pattern `{}`", pattern);
+        let pattern = pattern.to_string();
+        self.described_check(&description, &reason, move |value| glob_match(&pattern, value.as_ref()))
+    }
+
+    /// String value must be at least `min_len` characters long.
+    pub fn min_len(self, min_len : usize) -> Self {
+        self.constraint(&format!("at least {} characters long", min_len), move |value| value.as_ref().chars().count() >= min_len)
+    }
+
+    /// String value must be at most `max_len` characters long.
+    pub fn max_len(self, max_len : usize) -> Self {
+        self.constraint(&format!("at most {} characters long", max_len), move |value| value.as_ref().chars().count() <= max_len)
+    }
+}
+
+impl<T : Integer + ConstValue + 'static> ConstOptions<T> {
+    /// Value must be a power of two if `power_of_two` is true.
+    pub fn power_of_two(self, power_of_two : bool) -> Self {
+        if power_of_two {
+            self.constraint("a power of two", T::is_power_of_two)
+        } else {
+            self
+        }
+    }
+
+    /// Value must be a multiple of `multiple`.
+    pub fn multiple_of(self, multiple : T) -> Self {
+        let description = format!("a multiple of {}", multiple.to_env());
+        self.constraint(&description, move |value| value.is_multiple_of(&multiple))
+    }
+}
+
+/// Integers which can be checked with [ConstOptions::power_of_two] and [ConstOptions::multiple_of].
+pub trait Integer {
+    /// Value is a positive power of two.
+    fn is_power_of_two(&self) -> bool;
+
+    /// Value is a multiple of `multiple`. Only 0 is a multiple of 0.
+    fn is_multiple_of(&self, multiple : &Self) -> bool;
+}
+
+/// Implement [Integer] for primitive integers.
+macro_rules! integer {
+    ($($integer : ty),*) => {
+        $(
+            impl Integer for $integer {
+                #[allow(unused_comparisons)]
+                fn is_power_of_two(&self) -> bool {
+                    *self > 0 && *self & (*self - 1) == 0
+                }
+
+                fn is_multiple_of(&self, multiple : &Self) -> bool {
+                    if *multiple == 0 { *self == 0 } else { *self % *multiple == 0 }
+                }
+            }
+        )*
+    };
+}
+
+integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::{ ConstOptions, Integer };
+    use crate::Condition;
+
+    #[test]
+    fn env() {
+        assert_eq!(ConstOptions::<u8>::default().env, None);
+        assert_eq!(ConstOptions::<u8>::default().env("WIDTH").env.as_deref(), Some("WIDTH"));
+    }
+
+    #[test]
+    fn profile() {
+        let options = ConstOptions::<usize>::default().profile(("release", 10_000)).profile(("opt-level=nsoc", 100));
+        assert_eq!(options.defaults.len(), 2);
+
+        // Unit tests are not run by a build script.
+        assert_eq!(options.default_value(&10), (&10, None));
+
+        let options = options.target(("pointer_width=16", 256));
+        assert_eq!(options.defaults[2], (Condition::Target("pointer_width=16".to_string()), 256));
+    }
+
+    #[test]
+    fn range() {
+        let options = ConstOptions::<usize>::default().range(1..=64);
+        assert_eq!(options.constraints, vec![">= 1 and <= 64"]);
+        assert_eq!(options.validate(&1), Ok(()));
+        assert_eq!(options.validate(&64), Ok(()));
+        assert_eq!(options.validate(&0), Err("must be >= 1 and <= 64".to_string()));
+        assert_eq!(options.validate(&65), Err("must be >= 1 and <= 64".to_string()));
+
+        let options = ConstOptions::<usize>::default().range(1..64);
+        assert_eq!(options.constraints, vec![">= 1 and < 64"]);
+        assert!(options.validate(&64).is_err());
+    }
+
+    #[test]
+    fn range_half_open() {
+        let options = ConstOptions::<i32>::default().range(-10..);
+        assert_eq!(options.constraints, vec![">= -10"]);
+        assert!(options.validate(&i32::MAX).is_ok());
+        assert!(options.validate(&-11).is_err());
+
+        let options = ConstOptions::<f64>::default().range(..=1.0);
+        assert_eq!(options.constraints, vec!["<= 1.0"]);
+        assert!(options.validate(&1.0).is_ok());
+        assert!(options.validate(&1.5).is_err());
+
+        let options = ConstOptions::<u8>::default().range(..);
+        assert!(options.constraints.is_empty());
+        assert!(options.checks.is_empty());
+    }
+
+    #[test]
+    fn validator() {
+        fn not_reserved(port : &u16) -> Result<(), String> {
+            if [22, 80, 443].contains(port) { Err("must not be a reserved port".to_string()) } else { Ok(()) }
+        }
+
+        let options = ConstOptions::<u16>::default().range(1..).validator(not_reserved).validator(|port| if *port == 8080 { Err("is taken".to_string()) } else { Ok(()) });
+        assert!(options.constraints.len() == 1);
+        assert_eq!(options.validate(&8000), Ok(()));
+        assert_eq!(options.validate(&0), Err("must be >= 1".to_string()));
+        assert_eq!(options.validate(&443), Err("must not be a reserved port".to_string()));
+        assert_eq!(options.validate(&8080), Err("is taken".to_string()));
+    }
+
+    #[test]
+    fn pattern() {
+        let options = ConstOptions::<&str>::default().pattern("/api/*").min_len(5).max_len(8);
+        assert_eq!(options.constraints, vec!["matching pattern `/api/*`", "at least 5 characters long", "at most 8 characters long"]);
+        assert_eq!(options.validate(&"/api/v1"), Ok(()));
+        assert_eq!(options.validate(&"/v1"), Err("must match pattern `/api/*`".to_string()));
+        assert_eq!(options.validate(&"/api"), Err("must match pattern `/api/*`".to_string()));
+        assert_eq!(options.validate(&"/api/"), Ok(()));
+        assert_eq!(options.validate(&"/api/v1/users"), Err("must be at most 8 characters long".to_string()));
+
+        let options = ConstOptions::<String>::default().min_len(2);
+        assert_eq!(options.validate(&"é".to_string()), Err("must be at least 2 characters long".to_string()));
+    }
+
+    #[test]
+    fn power_of_two() {
+        let options = ConstOptions::<u32>::default().power_of_two(true);
+        assert_eq!(options.constraints, vec!["a power of two"]);
+        assert!(options.validate(&1024).is_ok());
+        assert_eq!(options.validate(&1000), Err("must be a power of two".to_string()));
+        assert!(options.validate(&0).is_err());
+        assert!(ConstOptions::<u32>::default().power_of_two(false).checks.is_empty());
+        assert!(!(-4i8).is_power_of_two());
+        assert!(64i8.is_power_of_two());
+    }
+
+    #[test]
+    fn multiple_of() {
+        let options = ConstOptions::<usize>::default().multiple_of(64).range(64..=4096);
+        assert_eq!(options.constraints, vec!["a multiple of 64", ">= 64 and <= 4096"]);
+        assert!(options.validate(&128).is_ok());
+        assert_eq!(options.validate(&100), Err("must be a multiple of 64".to_string()));
+        assert_eq!(options.validate(&0), Err("must be >= 64 and <= 4096".to_string()));
+        assert!(Integer::is_multiple_of(&0u8, &0));
+        assert!(!(-6i16).is_multiple_of(&4));
+    }
+}
@@ -0,0 +1,394 @@
+//! Minimal RON parser reading override values into a [Source].
+//! 
+//! The document must be a struct, named or not, or a map. Fields of nested structs and maps are
+//! flattened with `_`, so `(limits: (max_conn: 10))` sets `LIMITS_MAX_CONN`. Raw strings, byte strings
+//! and extensions are not supported.
+
+use std::ops::{ Deref, DerefMut };
+
+use crate::{ Source, SourceValue };
+use crate::cursor::Cursor;
+
+/// Parse the RON document `content` into `source`.
+/// 
+/// Lists and tuples of values override array and tuple constants, `Some(value)` is `value` and `None`
+/// an empty value so `Option` constants are `None`. Enum variants without fields are written as their
+/// name, so they can override enum constants.
+pub fn parse_ron(content : &str, source : &mut Source) -> Result<(), String> {
+    let mut parser = Parser(Cursor::new(content));
+    parser.document(source).map_err(|reason| parser.error(reason))
+}
+
+/// Value of a field before it is flattened in the [Source].
+enum Value {
+    Scalar(String),
+    List(Vec<String>),
+    Map(Vec<(String, Value)>),
+}
+
+/// Parser moving a [Cursor] over the document.
+struct Parser(Cursor);
+
+impl Deref for Parser {
+    type Target = Cursor;
+
+    fn deref(&self) -> &Cursor {
+        &self.0
+    }
+}
+
+impl DerefMut for Parser {
+    fn deref_mut(&mut self) -> &mut Cursor {
+        &mut self.0
+    }
+}
+
+impl Parser {
+    /// Skip whitespace and comments.
+    fn blank(&mut self) -> Result<(), String> {
+        loop {
+            if self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            } else if self.starts_with("//") {
+                while !matches!(self.peek(), None | Some('\n')) {
+                    self.pos += 1;
+                }
+            } else if self.starts_with("/*") {
+                self.pos += 2;
+                while !self.starts_with("*/") {
+                    if self.peek().is_none() {
+                        return Err("unterminated comment".to_string());
+                    }
+                    self.pos += 1;
+                }
+                self.pos += 2;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Consume `c` after whitespace if it is next.
+    fn eat(&mut self, c : char) -> Result<bool, String> {
+        self.blank()?;
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        Ok(found)
+    }
+
+    fn document(&mut self, source : &mut Source) -> Result<(), String> {
+        self.blank()?;
+        if self.starts_with("#![") {
+            return Err("extensions are not supported".to_string());
+        }
+
+        match self.value()? {
+            Value::Map(fields) => fields.into_iter().for_each(|(key, value)| insert(source, &key, value)),
+            // Struct without fields.
+            Value::List(elements) if elements.is_empty() => (),
+            _ => return Err("the document must be a struct or a map".to_string()),
+        }
+
+        self.blank()?;
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(format!("unexpected `{}` after the document", c)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.blank()?;
+        match self.peek() {
+            Some('"') => { self.pos += 1; self.string().map(Value::Scalar) },
+            Some('\'') => { self.pos += 1; self.character().map(Value::Scalar) },
+            Some('[') => { self.pos += 1; self.list(']') },
+            Some('{') => { self.pos += 1; self.map() },
+            Some('(') => { self.pos += 1; self.parenthesis() },
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.identifier();
+                match name.as_str() {
+                    "true" | "false" => Ok(Value::Scalar(name)),
+                    "inf" | "NaN" => Ok(Value::Scalar(name)),
+                    "None" => Ok(Value::Scalar(String::new())),
+                    "Some" if self.eat('(')? => {
+                        let value = self.value()?;
+                        self.eat(',')?;
+                        if !self.eat(')')? {
+                            return Err("expected `)` after the value of `Some`".to_string());
+                        }
+                        Ok(value)
+                    },
+                    // Named struct, tuple struct or enum variant with fields.
+                    _ if self.eat('(')? => self.parenthesis(),
+                    _ => Ok(Value::Scalar(name)),
+                }
+            },
+            Some(_) => self.number().map(Value::Scalar),
+            None => Err("expected value".to_string()),
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Parse a struct or a tuple after its opening parenthesis. A tuple of one value is that value.
+    fn parenthesis(&mut self) -> Result<Value, String> {
+        self.blank()?;
+        let start = self.pos;
+        let is_struct = self.peek().is_some_and(|c| c.is_alphabetic() || c == '_') && {
+            self.identifier();
+            let is_field = self.eat(':')?;
+            self.pos = start;
+            is_field
+        };
+
+        if !is_struct {
+            return match self.list(')')? {
+                Value::List(mut elements) if elements.len() == 1 => Ok(Value::Scalar(elements.remove(0))),
+                value => Ok(value),
+            };
+        }
+
+        let mut fields = Vec::new();
+        loop {
+            if self.eat(')')? {
+                return Ok(Value::Map(fields));
+            }
+
+            self.blank()?;
+            let name = self.identifier();
+            if name.is_empty() || !self.eat(':')? {
+                return Err("expected `field: value` in struct".to_string());
+            }
+            fields.push((name, self.value()?));
+
+            if !self.eat(',')? {
+                return match self.eat(')')? {
+                    true => Ok(Value::Map(fields)),
+                    false => Err("expected `,` or `)` in struct".to_string()),
+                };
+            }
+        }
+    }
+
+    /// Parse the elements of a list or a tuple until `end`.
+    fn list(&mut self, end : char) -> Result<Value, String> {
+        let mut elements = Vec::new();
+
+        loop {
+            if self.eat(end)? {
+                return Ok(Value::List(elements));
+            }
+
+            match self.value()? {
+                Value::Scalar(element) => elements.push(element),
+                _ => return Err("lists can only contain strings, numbers, booleans and enum variants".to_string()),
+            }
+
+            if !self.eat(',')? {
+                return match self.eat(end)? {
+                    true => Ok(Value::List(elements)),
+                    false => Err(format!("expected `,` or `{}` in list", end)),
+                };
+            }
+        }
+    }
+
+    /// Parse a map after its opening brace. Keys must be strings, numbers or identifiers.
+    fn map(&mut self) -> Result<Value, String> {
+        let mut entries = Vec::new();
+
+        loop {
+            if self.eat('}')? {
+                return Ok(Value::Map(entries));
+            }
+
+            let key = match self.value()? {
+                Value::Scalar(key) => key,
+                _ => return Err("map keys must be strings, numbers or identifiers".to_string()),
+            };
+            if !self.eat(':')? {
+                return Err("expected `:` after map key".to_string());
+            }
+            entries.push((key, self.value()?));
+
+            if !self.eat(',')? {
+                return match self.eat('}')? {
+                    true => Ok(Value::Map(entries)),
+                    false => Err("expected `,` or `}` in map".to_string()),
+                };
+            }
+        }
+    }
+
+    /// Parse a string after its opening quote.
+    fn string(&mut self) -> Result<String, String> {
+        let mut string = String::new();
+
+        loop {
+            match self.next_char("string")? {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// Parse a character after its opening quote.
+    fn character(&mut self) -> Result<String, String> {
+        let c = match self.next_char("character")? {
+            '\\' => self.escape()?,
+            c => c,
+        };
+
+        match self.next_char("character")? {
+            '\'' => Ok(c.to_string()),
+            _ => Err("characters must contain a single character".to_string()),
+        }
+    }
+
+    /// Next character of a string or character literal.
+    fn next_char(&mut self, literal : &str) -> Result<char, String> {
+        let c = self.peek().ok_or_else(|| format!("unterminated {}", literal))?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    /// Parse an escape after its backslash.
+    fn escape(&mut self) -> Result<char, String> {
+        Ok(match self.next_char("string")? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            c @ ('"' | '\'' | '\\') => c,
+            'u' => {
+                if self.next_char("string")? != '{' {
+                    return Err("expected `{` after `\\u`".to_string());
+                }
+                let mut hex = String::new();
+                loop {
+                    match self.next_char("string")? {
+                        '}' => break,
+                        c => hex.push(c),
+                    }
+                }
+                u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or_else(|| format!("invalid unicode escape `{}`", hex))?
+            },
+            c => return Err(format!("invalid escape `\\{}`", c)),
+        })
+    }
+
+    /// Parse a number, written like in an environment variable.
+    fn number(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | '_')) {
+            self.pos += 1;
+        }
+
+        let token : String = self.chars[start..self.pos].iter().collect();
+        let number = token.replace('_', "");
+        let (sign, digits) = match number.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", number.strip_prefix('+').unwrap_or(&number)),
+        };
+        let radix = |prefix : &str, radix : u32| digits.strip_prefix(prefix).and_then(|digits| u128::from_str_radix(digits, radix).ok());
+
+        if let Some(value) = radix("0x", 16).or_else(|| radix("0o", 8)).or_else(|| radix("0b", 2)) {
+            Ok(format!("{}{}", sign, value))
+        } else if matches!(digits, "inf" | "NaN") {
+            Ok(format!("{}{}", sign, digits))
+        } else if number.parse::<i128>().is_ok() || (number.parse::<f64>().is_ok() && digits.bytes().all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b))) {
+            Ok(number)
+        } else if token.is_empty() {
+            Err(format!("expected value, found `{}`", self.peek().unwrap_or(' ')))
+        } else {
+            Err(format!("invalid value `{}`", token))
+        }
+    }
+}
+
+/// Insert `value` in `source`, nested structs and maps being flattened.
+fn insert(source : &mut Source, key : &str, value : Value) {
+    match value {
+        Value::Scalar(value) => source.insert(key, SourceValue::Value(value)),
+        Value::List(elements) => source.insert(key, SourceValue::List(elements)),
+        Value::Map(fields) => fields.into_iter().for_each(|(field, value)| insert(source, &format!("{}_{}", key, field), value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_ron;
+    use crate::{ Source, SourceValue };
+
+    #[test]
+    fn values() {
+        let source = Source::parse("nsoc.ron", r#"
+            // Overrides
+            Config(
+                default_width: 300,
+                hex: 0xFF,
+                negative: -1_000,
+                ratio: 1.5e3, /* inline */
+                bounds: (-inf, +inf, inf),
+                enabled: true,
+                cache: None,
+                name: Some("nsoc \"quoted\" \u{e9}"),
+                separator: '\t',
+                level: Debug,
+                size: (80, 50),
+                ports: [80, 443, 8080,],
+                limits: Limits(max_conn: 10, log: (level: Warn)),
+                paths: { "home": "/home", "temp": "/tmp" },
+                id: Id(7),
+            )
+        "#, parse_ron).unwrap();
+
+        let get = |key| source.get(key).map(SourceValue::to_string);
+        assert_eq!(get("DEFAULT_WIDTH").as_deref(), Some("300"));
+        assert_eq!(get("HEX").as_deref(), Some("255"));
+        assert_eq!(get("NEGATIVE").as_deref(), Some("-1000"));
+        assert_eq!(get("RATIO").as_deref(), Some("1.5e3"));
+        assert_eq!(get("BOUNDS").as_deref(), Some("[-inf, inf, inf]"));
+        assert_eq!(get("ENABLED").as_deref(), Some("true"));
+        assert_eq!(get("CACHE").as_deref(), Some(""));
+        assert_eq!(get("NAME").as_deref(), Some("nsoc \"quoted\" é"));
+        assert_eq!(get("SEPARATOR").as_deref(), Some("\t"));
+        assert_eq!(get("LEVEL").as_deref(), Some("Debug"));
+        assert_eq!(get("SIZE").as_deref(), Some("[80, 50]"));
+        assert_eq!(get("SIZE_1").as_deref(), Some("50"));
+        assert_eq!(get("PORTS").as_deref(), Some("[80, 443, 8080]"));
+        assert_eq!(get("LIMITS_MAX_CONN").as_deref(), Some("10"));
+        assert_eq!(get("LIMITS_LOG_LEVEL").as_deref(), Some("Warn"));
+        assert_eq!(get("PATHS_HOME").as_deref(), Some("/home"));
+        assert_eq!(get("ID").as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn documents() {
+        assert_eq!(Source::parse("nsoc.ron", "{ \"width\": 1 }", parse_ron).unwrap().get("WIDTH").map(SourceValue::to_string).as_deref(), Some("1"));
+        assert_eq!(Source::parse("nsoc.ron", "()", parse_ron).unwrap().keys().count(), 0);
+        assert_eq!(Source::parse("nsoc.ron", "(a: 1)", parse_ron).unwrap().get("A").map(SourceValue::to_string).as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Source::parse("nsoc.ron", "[1]", parse_ron).unwrap_err(), "line 1: the document must be a struct or a map");
+        assert_eq!(Source::parse("nsoc.ron", "(\n  a: 1\n  b: 2)", parse_ron).unwrap_err(), "line 3: expected `,` or `)` in struct");
+        assert_eq!(Source::parse("nsoc.ron", "(a: [[1]])", parse_ron).unwrap_err(), "line 1: lists can only contain strings, numbers, booleans and enum variants");
+        assert_eq!(Source::parse("nsoc.ron", "(a: \"open)", parse_ron).unwrap_err(), "line 1: unterminated string");
+        assert_eq!(Source::parse("nsoc.ron", "(a: 'ab')", parse_ron).unwrap_err(), "line 1: characters must contain a single character");
+        assert_eq!(Source::parse("nsoc.ron", "(a: 1) x", parse_ron).unwrap_err(), "line 1: unexpected `x` after the document");
+        assert_eq!(Source::parse("nsoc.ron", "(a: 1x)", parse_ron).unwrap_err(), "line 1: invalid value `1x`");
+        assert_eq!(Source::parse("nsoc.ron", "#![enable(implicit_some)]\n(a: 1)", parse_ron).unwrap_err(), "line 1: extensions are not supported");
+        assert_eq!(Source::parse("nsoc.ron", "(a: 1 /* open", parse_ron).unwrap_err(), "line 1: unterminated comment");
+    }
+}
@@ -0,0 +1,167 @@
+//! Sources of override values read after the environment variables, i.e. configuration files.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{ Path, PathBuf };
+
+use crate::{ ConstValue, NsocError };
+
+/// Value of a key in a [Source].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceValue {
+    /// Value written like in an environment variable.
+    Value(String),
+
+    /// Elements of an array, each written like in an environment variable.
+    List(Vec<String>),
+}
+
+impl SourceValue {
+    /// Parse the value as `T`, splitting a [SourceValue::Value] with `delimiter` like an environment
+    /// variable while the elements of a [SourceValue::List] are parsed one by one.
+    pub fn parse<T : ConstValue>(&self, delimiter : &str) -> Result<T, String> {
+        match self {
+            SourceValue::Value(value) => T::parse_env_delimited(value, delimiter),
+            SourceValue::List(elements) => T::parse_elements(elements),
+        }
+    }
+}
+
+impl Display for SourceValue {
+    /// Lists are written between brackets, with their elements separated by `, `.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceValue::Value(value) => write!(f, "{}", value),
+            SourceValue::List(elements) => write!(f, "[{}]", elements.join(", ")),
+        }
+    }
+}
+
+/// Override values read from a file, looked up after the environment variables by
+/// [ConstFile](crate::ConstFile) in the order sources are added.
+/// 
+/// Keys are the names of the environment variables, with or without the prefix of the file, i.e.
+/// `NSLOG_DEFAULT_WIDTH` or `DEFAULT_WIDTH`.
+/// Nested keys are joined with `_`, so `max_conn` in section `limits` is `LIMITS_MAX_CONN`. Keys are
+/// case-insensitive, `-` and `.` being read as `_`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Source {
+    name : String,
+    values : BTreeMap<String, SourceValue>,
+}
+
+impl Source {
+    /// Create an empty source named `name`, usually the path of its file.
+    pub fn new(name : &str) -> Self {
+        Source { name: name.to_string(), values: BTreeMap::new() }
+    }
+
+    /// Name of the source.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Set the value of `key`, replacing any previous value.
+    /// 
+    /// Elements of a [SourceValue::List] are also set as `{key}_{index}` to override tuples field by field.
+    pub fn insert(&mut self, key : &str, value : SourceValue) {
+        let key = Self::normalize(key);
+
+        if let SourceValue::List(elements) = &value {
+            for (index, element) in elements.iter().enumerate() {
+                self.values.insert(format!("{}_{}", key, index), SourceValue::Value(element.clone()));
+            }
+        }
+
+        self.values.insert(key, value);
+    }
+
+    /// Value of `key`.
+    pub fn get(&self, key : &str) -> Option<&SourceValue> {
+        self.values.get(&Self::normalize(key))
+    }
+
+    /// Keys with a value.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(|key| key.as_str())
+    }
+
+    /// Read the file at `path` and parse it with `parse`.
+    /// 
+    /// Returns `Ok(None)` if the file does not exist. An existing file is watched with
+    /// `cargo:rerun-if-changed`, so editing it rebuilds the crate. A missing file is not watched, see
+    /// [ConstFile::watch_missing](crate::ConstFile::watch_missing).
+    pub fn read(path : &Path, parse : impl FnOnce(&str, &mut Source) -> Result<(), String>) -> Result<Option<Self>, NsocError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        println!("cargo:rerun-if-changed={}", path.display());
+        let invalid = |reason : String| NsocError::InvalidSource { path: path.to_path_buf(), reason };
+        let content = std::fs::read_to_string(path).map_err(|error| invalid(error.to_string()))?;
+        Self::parse(&path.display().to_string(), &content, parse).map(Some).map_err(invalid)
+    }
+
+    /// Source named `name` with the values of `content`, parsed with `parse`.
+    pub fn parse(name : &str, content : &str, parse : impl FnOnce(&str, &mut Source) -> Result<(), String>) -> Result<Self, String> {
+        let mut source = Source::new(name);
+        parse(content, &mut source).map(|_| source)
+    }
+
+    /// Normalized key, in upper case with `-` and `.` replaced by `_`.
+    fn normalize(key : &str) -> String {
+        key.to_uppercase().replace(['-', '.'], "_")
+    }
+}
+
+/// Path relative to the package root when `CARGO_MANIFEST_DIR` is set, as in build scripts.
+pub(crate) fn package_path(path : &Path) -> PathBuf {
+    match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) if path.is_relative() => PathBuf::from(manifest_dir).join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ Source, SourceValue };
+    use crate::NsocError;
+
+    #[test]
+    fn values() {
+        let mut source = Source::new("test");
+        source.insert("default_width", SourceValue::Value("300".to_string()));
+        source.insert("log-targets", SourceValue::List(vec!["stdout".to_string(), "file".to_string()]));
+
+        assert_eq!(source.name(), "test");
+        assert_eq!(source.get("DEFAULT_WIDTH"), Some(&SourceValue::Value("300".to_string())));
+        assert_eq!(source.get("LOG_TARGETS"), Some(&SourceValue::List(vec!["stdout".to_string(), "file".to_string()])));
+        assert_eq!(source.get("LOG_TARGETS_1"), Some(&SourceValue::Value("file".to_string())));
+        assert_eq!(source.get("DEFAULT_HEIGHT"), None);
+        assert_eq!(source.keys().collect::<Vec<&str>>(), vec!["DEFAULT_WIDTH", "LOG_TARGETS", "LOG_TARGETS_0", "LOG_TARGETS_1"]);
+    }
+
+    #[test]
+    fn parse() {
+        // Elements of lists are never split by the delimiter nor trimmed.
+        let list = SourceValue::List(vec!["a,b".to_string(), " padded ".to_string()]);
+        assert_eq!(list.parse::<Vec<&str>>(","), Ok(vec!["a,b", " padded "]));
+        assert_eq!(list.to_string(), "[a,b,  padded ]");
+        assert_eq!(SourceValue::Value("a,b".to_string()).parse::<Vec<&str>>(","), Ok(vec!["a", "b"]));
+        assert_eq!(SourceValue::Value("a, b".to_string()).parse::<String>(","), Ok("a, b".to_string()));
+        assert_eq!(list.parse::<String>(","), Err("expected a single value, found a list of 2 elements".to_string()));
+    }
+
+    #[test]
+    fn read() {
+        let dir = std::env::temp_dir().join("nsoc_tests_source");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("source.txt"), "WIDTH").unwrap();
+
+        let source = Source::read(&dir.join("source.txt"), |content, source| { source.insert(content, SourceValue::Value("1".to_string())); Ok(()) }).unwrap().unwrap();
+        assert_eq!(source.get("WIDTH"), Some(&SourceValue::Value("1".to_string())));
+        assert_eq!(Source::read(&dir.join("missing.txt"), |_, _| Ok(())), Ok(None));
+        assert_eq!(Source::read(&dir.join("source.txt"), |_, _| Err("line 1: invalid".to_string())),
+            Err(NsocError::InvalidSource { path: dir.join("source.txt"), reason: "line 1: invalid".to_string() }));
+    }
+}
@@ -0,0 +1,503 @@
+//! Minimal TOML parser reading override values into a [Source].
+//! 
+//! Supports tables, arrays of tables, dotted and quoted keys, basic, literal and multi-line strings,
+//! integers, floats, booleans, arrays of these values and inline tables. Dates and arrays of arrays or
+//! of tables are not supported.
+
+use std::collections::HashMap;
+use std::ops::{ Deref, DerefMut };
+
+use crate::{ Source, SourceValue };
+use crate::cursor::Cursor;
+
+/// Parse the TOML document `content` into `source`, nested keys being joined with `_`.
+/// 
+/// Tables of an array of tables are numbered from 0, so `name` in the second `[[constants]]` is
+/// `CONSTANTS_1_NAME`.
+pub fn parse_toml(content : &str, source : &mut Source) -> Result<(), String> {
+    let mut parser = Parser(Cursor::new(content));
+    parser.document(source, &[]).map(|_| ()).map_err(|reason| parser.error(reason))
+}
+
+/// Parse the table `table` of the TOML document `content` into `source`, i.e. `package.metadata.nsoc`,
+/// its keys being relative to it, and return whether the document has this table.
+/// 
+/// If `table` is the key of a value, i.e. `package.workspace`, the value is read with an empty key.
+/// The whole document is parsed, but values the parser does not support are only rejected inside `table`.
+pub(crate) fn parse_toml_table(content : &str, table : &str, source : &mut Source) -> Result<bool, String> {
+    let root : Vec<String> = table.split('.').map(str::to_string).collect();
+    let mut parser = Parser(Cursor::new(content));
+    parser.document(source, &root).map_err(|reason| parser.error(reason))
+}
+
+/// Value of a key before it is flattened in the [Source].
+enum Value {
+    Scalar(String),
+    Array(Vec<String>),
+    Table(Vec<(Vec<String>, Value)>),
+    /// Value a [Source] cannot hold, with the reason.
+    Unsupported(String),
+}
+
+/// Parser moving a [Cursor] over the document.
+struct Parser(Cursor);
+
+impl Deref for Parser {
+    type Target = Cursor;
+
+    fn deref(&self) -> &Cursor {
+        &self.0
+    }
+}
+
+impl DerefMut for Parser {
+    fn deref_mut(&mut self) -> &mut Cursor {
+        &mut self.0
+    }
+}
+
+impl Parser {
+    /// Skip spaces and tabs.
+    fn spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip spaces, newlines and comments.
+    fn blank(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r' | '\n') => self.pos += 1,
+                Some('#') => self.comment(),
+                _ => return,
+            }
+        }
+    }
+
+    /// Skip a comment up to the end of the line.
+    fn comment(&mut self) {
+        while !matches!(self.peek(), None | Some('\n')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Expect the end of the line after a table header or a key/value pair.
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.spaces();
+        if self.peek() == Some('#') {
+            self.comment();
+        }
+
+        match self.next() {
+            None | Some('\n') => Ok(()),
+            Some('\r') if self.peek() == Some('\n') => { self.pos += 1; Ok(()) },
+            Some(c) => Err(format!("expected end of line, found `{}`", c)),
+        }
+    }
+
+    /// Parse the document, inserting the keys in `root` into `source`, and return whether any is in `root`.
+    fn document(&mut self, source : &mut Source, root : &[String]) -> Result<bool, String> {
+        let mut found = false;
+        let mut table = Vec::new();
+        let mut array_lengths : HashMap<Vec<String>, usize> = HashMap::new();
+
+        loop {
+            self.blank();
+            match self.peek() {
+                None => return Ok(found),
+                Some('[') if self.starts_with("[[") => {
+                    self.pos += 2;
+                    self.spaces();
+                    let array = self.key()?;
+                    self.spaces();
+                    if !self.starts_with("]]") {
+                        return Err("expected `]]` after array of tables name".to_string());
+                    }
+                    self.pos += 2;
+
+                    let length = array_lengths.entry(array.clone()).or_default();
+                    table = array;
+                    table.push(length.to_string());
+                    *length += 1;
+                    found |= table.starts_with(root);
+                },
+                Some('[') => {
+                    self.pos += 1;
+                    self.spaces();
+                    table = self.key()?;
+                    self.spaces();
+                    if self.next() != Some(']') {
+                        return Err("expected `]` after table name".to_string());
+                    }
+                    found |= table.starts_with(root);
+                },
+                Some(_) => {
+                    let key : Vec<String> = table.iter().cloned().chain(self.key()?).collect();
+                    let value = self.key_value()?;
+                    found |= key.starts_with(root);
+                    select(source, root, key, value)?;
+                },
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    /// Parse `= value` after a key.
+    fn key_value(&mut self) -> Result<Value, String> {
+        self.spaces();
+        if self.next() != Some('=') {
+            return Err("expected `=` after key".to_string());
+        }
+
+        self.spaces();
+        self.value()
+    }
+
+    /// Parse a dotted key into its parts.
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut parts = Vec::new();
+
+        loop {
+            let part = match self.peek() {
+                Some('"') => { self.pos += 1; self.basic_string()? },
+                Some('\'') => { self.pos += 1; self.literal_string()? },
+                _ => {
+                    let start = self.pos;
+                    while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                        self.pos += 1;
+                    }
+
+                    if start == self.pos {
+                        return Err(match self.peek() {
+                            Some(c) => format!("expected key, found `{}`", c),
+                            None => "expected key".to_string(),
+                        });
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                },
+            };
+            parts.push(part);
+
+            self.spaces();
+            if self.peek() != Some('.') {
+                return Ok(parts);
+            }
+            self.pos += 1;
+            self.spaces();
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) if self.starts_with(&quote.to_string().repeat(3)) => { self.pos += 3; self.multi_line_string(quote).map(Value::Scalar) },
+            Some('"') => { self.pos += 1; self.basic_string().map(Value::Scalar) },
+            Some('\'') => { self.pos += 1; self.literal_string().map(Value::Scalar) },
+            Some('[') => { self.pos += 1; self.array() },
+            Some('{') => { self.pos += 1; self.inline_table() },
+            _ => self.bare_value(),
+        }
+    }
+
+    /// Parse a basic string after its opening quote, with escapes.
+    fn basic_string(&mut self) -> Result<String, String> {
+        let mut string = String::new();
+
+        loop {
+            match self.string_char()? {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// Parse an escape after its backslash.
+    fn escape(&mut self) -> Result<char, String> {
+        Ok(match self.string_char()? {
+            'b' => '\u{8}',
+            't' => '\t',
+            'n' => '\n',
+            'f' => '\u{c}',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            'u' => self.unicode(4)?,
+            'U' => self.unicode(8)?,
+            c => return Err(format!("invalid escape `\\{}`", c)),
+        })
+    }
+
+    /// Parse a multi-line string after its three opening `quote`, with escapes for basic strings.
+    /// 
+    /// A newline right after the opening quotes is trimmed, and so is the whitespace after a backslash
+    /// ending a line of a basic string.
+    fn multi_line_string(&mut self, quote : char) -> Result<String, String> {
+        let mut string = String::new();
+        if self.starts_with("\r\n") {
+            self.pos += 2;
+        } else if self.peek() == Some('\n') {
+            self.pos += 1;
+        }
+
+        loop {
+            // Up to two quotes can end the string right before the closing ones.
+            let quotes = self.chars[self.pos..].iter().take_while(|c| **c == quote).count();
+            if quotes >= 3 {
+                string.push_str(&quote.to_string().repeat((quotes - 3).min(2)));
+                self.pos += quotes.min(5);
+                return Ok(string);
+            }
+
+            match self.next() {
+                None => return Err("unterminated string".to_string()),
+                Some('\\') if quote == '"' => {
+                    let spaces = self.chars[self.pos..].iter().take_while(|c| matches!(c, ' ' | '\t' | '\r')).count();
+                    if matches!(self.chars.get(self.pos + spaces), Some('\n')) {
+                        while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+                            self.pos += 1;
+                        }
+                    } else {
+                        string.push(self.escape()?);
+                    }
+                },
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// Next character of a string, which cannot span lines.
+    fn string_char(&mut self) -> Result<char, String> {
+        match self.peek() {
+            None | Some('\n') => Err("unterminated string".to_string()),
+            Some(c) => { self.pos += 1; Ok(c) },
+        }
+    }
+
+    /// Parse the `digits` hexadecimal digits of a unicode escape.
+    fn unicode(&mut self, digits : usize) -> Result<char, String> {
+        let hex = (0..digits).map(|_| self.string_char()).collect::<Result<String, String>>()?;
+        u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or_else(|| format!("invalid unicode escape `{}`", hex))
+    }
+
+    /// Parse a literal string after its opening quote, without escapes.
+    fn literal_string(&mut self) -> Result<String, String> {
+        let mut string = String::new();
+
+        loop {
+            match self.string_char()? {
+                '\'' => return Ok(string),
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// Parse an array after its opening bracket. Elements can be on several lines.
+    fn array(&mut self) -> Result<Value, String> {
+        let mut elements = Vec::new();
+
+        loop {
+            self.blank();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                break;
+            }
+
+            elements.push(self.value()?);
+
+            self.blank();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => break,
+                _ => return Err("expected `,` or `]` in array".to_string()),
+            }
+        }
+
+        let scalars = elements.into_iter().map(|element| match element {
+            Value::Scalar(element) => Some(element),
+            _ => None,
+        }).collect::<Option<Vec<String>>>();
+        Ok(scalars.map_or_else(|| Value::Unsupported("arrays can only contain strings, numbers and booleans".to_string()), Value::Array))
+    }
+
+    /// Parse an inline table after its opening brace.
+    fn inline_table(&mut self) -> Result<Value, String> {
+        let mut values = Vec::new();
+
+        self.spaces();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Table(values));
+        }
+
+        loop {
+            self.spaces();
+            let key = self.key()?;
+            values.push((key, self.key_value()?));
+
+            self.spaces();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Table(values)),
+                _ => return Err("expected `,` or `}` in inline table".to_string()),
+            }
+        }
+    }
+
+    /// Parse a boolean or a number, written like in an environment variable.
+    /// 
+    /// Other tokens, i.e. dates, are unsupported values.
+    fn bare_value(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if !c.is_whitespace() && !matches!(c, ',' | ']' | '}' | '#')) {
+            self.pos += 1;
+        }
+
+        let token : String = self.chars[start..self.pos].iter().collect();
+        let number = token.replace('_', "");
+        let radix = |prefix : &str, radix : u32| number.strip_prefix(prefix).and_then(|digits| u128::from_str_radix(digits, radix).ok());
+
+        Ok(match token.as_str() {
+            "" => return Err("expected value".to_string()),
+            "true" | "false" => Value::Scalar(token),
+            "inf" | "+inf" => Value::Scalar("inf".to_string()),
+            "-inf" => Value::Scalar("-inf".to_string()),
+            "nan" | "+nan" | "-nan" => Value::Scalar("NaN".to_string()),
+            _ => if let Some(value) = radix("0x", 16).or_else(|| radix("0o", 8)).or_else(|| radix("0b", 2)) {
+                Value::Scalar(value.to_string())
+            } else if number.parse::<i128>().is_ok() || (number.parse::<f64>().is_ok() && number.bytes().all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b))) {
+                Value::Scalar(number)
+            } else {
+                Value::Unsupported(format!("invalid value `{}`", token))
+            },
+        })
+    }
+}
+
+/// Insert `value` of `key` in `source` if it is in the table `root`, keys being relative to it.
+/// 
+/// Inline tables containing `root` are searched, and unsupported values outside of `root` are ignored.
+fn select(source : &mut Source, root : &[String], key : Vec<String>, value : Value) -> Result<(), String> {
+    match key.strip_prefix(root) {
+        Some(relative) => insert(source, &relative.join("_"), value),
+        None => match value {
+            Value::Table(values) if root.starts_with(&key) => values.into_iter()
+                .try_for_each(|(field, value)| select(source, root, key.iter().cloned().chain(field).collect(), value)),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Insert `value` in `source`, inline tables being flattened like tables.
+fn insert(source : &mut Source, key : &str, value : Value) -> Result<(), String> {
+    match value {
+        Value::Scalar(value) => source.insert(key, SourceValue::Value(value)),
+        Value::Array(elements) => source.insert(key, SourceValue::List(elements)),
+        Value::Table(values) => return values.into_iter().try_for_each(|(field, value)| {
+            let field = field.join("_");
+            insert(source, &if key.is_empty() { field } else { format!("{}_{}", key, field) }, value)
+        }),
+        Value::Unsupported(reason) => return Err(reason),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ parse_toml, parse_toml_table };
+    use crate::{ Source, SourceValue };
+
+    #[test]
+    fn values() {
+        let source = Source::parse("nsoc.toml", "# Overrides\n\
+            default_width = 300 # Frame\n\
+            ratio = 1.5e3\n\
+            hex = 0xFF\n\
+            big = 1_000_000\n\
+            negative = -1\n\
+            enabled = true\n\
+            name = \"nsoc \\\"quoted\\\" \\u00e9\"\n\
+            path = 'C:\\path'\n\
+            ports = [80, 443,\n  8080, # Alternative\n]\n\
+            empty = []\n\
+            size = { width = 80, height = 50 }\n", parse_toml).unwrap();
+
+        let get = |key| source.get(key).map(SourceValue::to_string);
+        assert_eq!(get("DEFAULT_WIDTH").as_deref(), Some("300"));
+        assert_eq!(get("RATIO").as_deref(), Some("1.5e3"));
+        assert_eq!(get("HEX").as_deref(), Some("255"));
+        assert_eq!(get("BIG").as_deref(), Some("1000000"));
+        assert_eq!(get("NEGATIVE").as_deref(), Some("-1"));
+        assert_eq!(get("ENABLED").as_deref(), Some("true"));
+        assert_eq!(get("NAME").as_deref(), Some("nsoc \"quoted\" é"));
+        assert_eq!(get("PATH").as_deref(), Some("C:\\path"));
+        assert_eq!(get("PORTS").as_deref(), Some("[80, 443, 8080]"));
+        assert_eq!(get("PORTS_2").as_deref(), Some("8080"));
+        assert_eq!(get("EMPTY").as_deref(), Some("[]"));
+        assert_eq!(get("SIZE_WIDTH").as_deref(), Some("80"));
+        assert_eq!(get("SIZE_HEIGHT").as_deref(), Some("50"));
+    }
+
+    #[test]
+    fn tables() {
+        let source = Source::parse("nsoc.toml", "top = 1\r\n[limits]\r\nmax_conn = 10\r\n[log.file]\nname = \"a\"\n\"quoted key\" = 2\nsub.key = 3\n", parse_toml).unwrap();
+
+        assert_eq!(source.keys().collect::<Vec<&str>>(), vec!["LIMITS_MAX_CONN", "LOG_FILE_NAME", "LOG_FILE_QUOTED KEY", "LOG_FILE_SUB_KEY", "TOP"]);
+    }
+
+    #[test]
+    fn arrays_of_tables() {
+        let source = Source::parse("nsoc.toml", "[[constants]]\nname = \"A\"\n[[other]]\nname = \"B\"\n[[constants]]\nname = \"C\"\n", parse_toml).unwrap();
+
+        assert_eq!(source.keys().collect::<Vec<&str>>(), vec!["CONSTANTS_0_NAME", "CONSTANTS_1_NAME", "OTHER_0_NAME"]);
+        assert_eq!(source.get("CONSTANTS_1_NAME").map(SourceValue::to_string).as_deref(), Some("C"));
+    }
+
+    #[test]
+    fn multi_line_strings() {
+        let source = Source::parse("nsoc.toml", "basic = \"\"\"\nLine \\\n   one\\t\"\"\"\"\n\
+            literal = '''\r\nC:\\path\n\"'''\n", parse_toml).unwrap();
+
+        let get = |key| source.get(key).map(SourceValue::to_string);
+        assert_eq!(get("BASIC").as_deref(), Some("Line one\t\""));
+        assert_eq!(get("LITERAL").as_deref(), Some("C:\\path\n\""));
+    }
+
+    #[test]
+    fn table() {
+        let parse = |content : &str, source : &mut Source| parse_toml_table(content, "package.metadata.nsoc", source).map(|_| ());
+        let content = "[package]\n\
+            values = [\n  [1, 2],\n]\n\
+            released = 1979-05-27\n\
+            metadata.nsoc.prefix = \"APP_\"\n\
+            [package.metadata.nsoc.limits]\n\
+            max_conn = 10\n\
+            [package.metadata.other]\n\
+            name = \"other\"\n";
+        let source = Source::parse("Cargo.toml", content, parse).unwrap();
+
+        assert_eq!(source.keys().collect::<Vec<&str>>(), vec!["LIMITS_MAX_CONN", "PREFIX"]);
+        assert_eq!(Source::parse("Cargo.toml", "package = { metadata = { nsoc = { a = 1 } } }", parse).unwrap().keys().collect::<Vec<&str>>(), vec!["A"]);
+        assert_eq!(Source::parse("Cargo.toml", "[package.metadata.nsoc]\na = [[1]]", parse).unwrap_err(), "line 2: arrays can only contain strings, numbers and booleans");
+        assert_eq!(Source::parse("Cargo.toml", "[package]\nworkspace = \"..\"", |content, source| parse_toml_table(content, "package.workspace", source).map(|_| ())).unwrap().get(""),
+            Some(&SourceValue::Value("..".to_string())));
+
+        let mut source = Source::new("Cargo.toml");
+        assert_eq!(parse_toml_table("[package]\nname = \"a\"\n[workspace]\n", "workspace", &mut source), Ok(true));
+        assert_eq!(parse_toml_table("[package]\nname = \"a\"\n", "workspace", &mut source), Ok(false));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Source::parse("nsoc.toml", "a = 1\nb = 2 3", parse_toml).unwrap_err(), "line 2: expected end of line, found `3`");
+        assert_eq!(Source::parse("nsoc.toml", "a = \"open", parse_toml).unwrap_err(), "line 1: unterminated string");
+        assert_eq!(Source::parse("nsoc.toml", "a = 'open\nb = 1", parse_toml).unwrap_err(), "line 1: unterminated string");
+        assert_eq!(Source::parse("nsoc.toml", "\n\na = 1979-05-27", parse_toml).unwrap_err(), "line 3: invalid value `1979-05-27`");
+        assert_eq!(Source::parse("nsoc.toml", "a = [[1]]", parse_toml).unwrap_err(), "line 1: arrays can only contain strings, numbers and booleans");
+        assert_eq!(Source::parse("nsoc.toml", "[[servers]\n", parse_toml).unwrap_err(), "line 1: expected `]]` after array of tables name");
+        assert_eq!(Source::parse("nsoc.toml", "a 1", parse_toml).unwrap_err(), "line 1: expected `=` after key");
+        assert_eq!(Source::parse("nsoc.toml", "= 1", parse_toml).unwrap_err(), "line 1: expected key, found `=`");
+        assert_eq!(Source::parse("nsoc.toml", "a = '''\nopen''", parse_toml).unwrap_err(), "line 2: unterminated string");
+        assert_eq!(Source::parse("nsoc.toml", "a = \"\\q\"", parse_toml).unwrap_err(), "line 1: invalid escape `\\q`");
+    }
+}
@@ -0,0 +1,737 @@
+//! Conversion of constant values into Rust literals.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{ Env, NsocError, Override };
+
+/// Default delimiter between elements of arrays and slices in environment variables.
+pub const DEFAULT_DELIMITER: &str = ",";
+
+/// Value of a constant written by [write_const!](crate::write_const).
+/// 
+/// Implemented for the types that can be overridden at compilation.
+/// 
+/// Arrays `[T; N]` are written as fixed-size arrays while `Vec<T>` and `&'static [T]` are written as
+/// `&'static [T]` slices. Their elements are separated by a delimiter in environment variables.
+/// 
+/// Tuples are overridden field by field, each from the variable `{env_var}_{index}`.
+/// 
+/// Integers are also parsed from numbers written with a fraction or an exponent whose value is an integer,
+/// i.e. `1e3` or `1.0`.
+/// 
+/// `Option<T>` is `None` when the environment variable is empty or `none`, in any case. A `Some` default is
+/// kept when the variable is `some`, and an `Option` of a tuple is then overridden field by field like when
+/// its variable is not set.
+/// A `None` default has no fields to override, so it can only be kept or set to `none`.
+pub trait ConstValue : Sized + Clone {
+    /// Type written in the constant declaration.
+    fn const_type() -> String;
+
+    /// Rust literal written as the constant value.
+    fn to_literal(&self) -> String;
+
+    /// Value as written in an environment variable.
+    fn to_env(&self) -> String;
+
+    /// Parse the value of an environment variable.
+    fn parse_env(value : &str) -> Result<Self, String>;
+
+    /// Value as written in an environment variable, with list elements separated by `delimiter`.
+    fn to_env_delimited(&self, _delimiter : &str) -> String {
+        self.to_env()
+    }
+
+    /// Parse the value of an environment variable, with list elements separated by `delimiter`.
+    fn parse_env_delimited(value : &str, _delimiter : &str) -> Result<Self, String> {
+        Self::parse_env(value)
+    }
+
+    /// Parse the elements of an array read from a [Source](crate::Source), each element being parsed
+    /// on its own with [ConstValue::parse_env].
+    /// 
+    /// Only lists can be parsed from elements, other types fail.
+    fn parse_elements(elements : &[String]) -> Result<Self, String> {
+        Err(format!("expected a single value, found a list of {} elements", elements.len()))
+    }
+
+    /// Value overridden by the environment variable `env_var` if `env` returns its value, else `default`.
+    /// 
+    /// Errors are [NsocError::InvalidOverride] without constant name, set by the caller with [NsocError::with_constant].
+    fn resolve(default : &Self, env_var : &str, delimiter : &str, env : &mut Env) -> Result<Self, NsocError> {
+        match env(env_var)? {
+            Some(Override { value, source }) => value.parse(delimiter).map_err(|reason| NsocError::InvalidOverride {
+                constant: String::new(), env_var: env_var.to_string(), source, value: value.to_string(), expected: Self::const_type(), default: default.to_literal(), reason }),
+            None => Ok(default.clone()),
+        }
+    }
+
+    /// Environment variables overriding the value when resolved from `env_var`, with the value as written in each of them.
+    fn env_overrides(&self, env_var : &str, delimiter : &str) -> Vec<(String, String)> {
+        vec![(env_var.to_string(), self.to_env_delimited(delimiter))]
+    }
+}
+
+/// Implement [ConstValue] for types where the literal is the [Display] output, parsing values with `$parse`.
+macro_rules! display_const_value {
+    ($parse : ident : $($value_type : ty),*) => {
+        $(
+            impl ConstValue for $value_type {
+                fn const_type() -> String {
+                    stringify!($value_type).to_string()
+                }
+
+                fn to_literal(&self) -> String {
+                    self.to_string()
+                }
+
+                fn to_env(&self) -> String {
+                    self.to_string()
+                }
+
+                fn parse_env(value : &str) -> Result<Self, String> {
+                    $parse(value)
+                }
+            }
+        )*
+    };
+}
+
+display_const_value!(parse_integer : u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+display_const_value!(parse_display : bool);
+
+/// Parse `value` with the [FromStr] implementation of `T`.
+fn parse_display<T : FromStr>(value : &str) -> Result<T, String> where T::Err : Display {
+    value.parse().map_err(|err : T::Err| err.to_string())
+}
+
+/// Parse the integer `value`, or else its [integral] form.
+fn parse_integer<T : FromStr>(value : &str) -> Result<T, String> where T::Err : Display {
+    value.parse().or_else(|err| integral(value).ok_or(err)?.parse()).map_err(|err : T::Err| err.to_string())
+}
+
+/// Integer form of the number `token` written with a fraction or an exponent whose value is an integer,
+/// i.e. `1e3` is `1000` and `1.0` is `1`, as numbers can be written in JSON.
+/// 
+/// Digits are shifted without converting to a float, so large integers keep their precision. Negative
+/// zero and integers longer than any integer type are kept as written.
+fn integral(token : &str) -> Option<String> {
+    let (negative, unsigned) = match token.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, token),
+    };
+    if !unsigned.bytes().all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b)) {
+        return None;
+    }
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(e) => (&unsigned[..e], unsigned[e + 1..].parse::<i32>().ok()?),
+        None => (unsigned, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if fraction.is_empty() && exponent == 0 {
+        return None;
+    }
+
+    // Position of the decimal point in the digits once the exponent is applied.
+    let digits = format!("{}{}", integer, fraction);
+    let point = integer.len() as i64 + exponent as i64;
+    let digits = if point >= digits.len() as i64 {
+        if point > 40 {
+            return None;
+        }
+        format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        let point = point.max(0) as usize;
+        if digits[point..].bytes().any(|b| b != b'0') {
+            return None;
+        }
+        digits[..point].to_string()
+    };
+
+    let digits = digits.trim_start_matches('0');
+    match (digits.is_empty(), negative) {
+        (true, true) => None,
+        (true, false) => Some("0".to_string()),
+        (false, true) => Some(format!("-{}", digits)),
+        (false, false) => Some(digits.to_string()),
+    }
+}
+
+
+/// Implement [ConstValue] for floats, written with their suffix so they never type-check as integers.
+/// 
+/// The [Debug](std::fmt::Debug) output is used since it is the shortest representation that parse
+/// back to the same value. Not a number and infinities are written with their associated constants.
+macro_rules! float_const_value {
+    ($($value_type : ident),*) => {
+        $(
+            impl ConstValue for $value_type {
+                fn const_type() -> String {
+                    stringify!($value_type).to_string()
+                }
+
+                fn to_literal(&self) -> String {
+                    if self.is_nan() {
+                        format!("{}::NAN", stringify!($value_type))
+                    } else if self.is_infinite() {
+                        format!("{}::{}", stringify!($value_type), if self.is_sign_positive() { "INFINITY" } else { "NEG_INFINITY" })
+                    } else {
+                        format!("{:?}{}", self, stringify!($value_type))
+                    }
+                }
+
+                fn to_env(&self) -> String {
+                    format!("{:?}", self)
+                }
+
+                fn parse_env(value : &str) -> Result<Self, String> {
+                    value.parse::<$value_type>().map_err(|err| err.to_string())
+                }
+            }
+        )*
+    };
+}
+
+float_const_value!(f32, f64);
+
+impl ConstValue for char {
+    fn const_type() -> String {
+        "char".to_string()
+    }
+
+    fn to_literal(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn to_env(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse_env(value : &str) -> Result<Self, String> {
+        value.parse::<char>().map_err(|err| err.to_string())
+    }
+}
+
+impl ConstValue for String {
+    /// Strings are always written as `&'static str` since constants cannot allocate.
+    fn const_type() -> String {
+        "&'static str".to_string()
+    }
+
+    fn to_literal(&self) -> String {
+        str_literal(self)
+    }
+
+    fn to_env(&self) -> String {
+        self.clone()
+    }
+
+    fn parse_env(value : &str) -> Result<Self, String> {
+        Ok(value.to_string())
+    }
+}
+
+impl ConstValue for &'static str {
+    fn const_type() -> String {
+        String::const_type()
+    }
+
+    fn to_literal(&self) -> String {
+        str_literal(self)
+    }
+
+    fn to_env(&self) -> String {
+        self.to_string()
+    }
+
+    /// The value is leaked, which is harmless in a build script.
+    fn parse_env(value : &str) -> Result<Self, String> {
+        Ok(Box::leak(value.to_string().into_boxed_str()))
+    }
+}
+
+impl<T : ConstValue, const N : usize> ConstValue for [T; N] {
+    fn const_type() -> String {
+        format!("[{}; {}]", T::const_type(), N)
+    }
+
+    fn to_literal(&self) -> String {
+        list_literal(self)
+    }
+
+    fn to_env(&self) -> String {
+        self.to_env_delimited(DEFAULT_DELIMITER)
+    }
+
+    fn parse_env(value : &str) -> Result<Self, String> {
+        Self::parse_env_delimited(value, DEFAULT_DELIMITER)
+    }
+
+    fn to_env_delimited(&self, delimiter : &str) -> String {
+        list_env(self, delimiter)
+    }
+
+    /// Fails if the count of elements is not `N`.
+    fn parse_env_delimited(value : &str, delimiter : &str) -> Result<Self, String> {
+        let elements = parse_list::<T>(value, delimiter)?;
+        let count = elements.len();
+        elements.try_into().map_err(|_| format!("expected {} elements separated by `{}`, found {}", N, delimiter, count))
+    }
+
+    /// Fails if the count of elements is not `N`.
+    fn parse_elements(elements : &[String]) -> Result<Self, String> {
+        let count = elements.len();
+        parse_each::<T>(elements)?.try_into().map_err(|_| format!("expected {} elements, found {}", N, count))
+    }
+}
+
+impl<T : ConstValue> ConstValue for Vec<T> {
+    /// Vectors are written as `&'static [T]` since constants cannot allocate.
+    fn const_type() -> String {
+        format!("&'static [{}]", T::const_type())
+    }
+
+    fn to_literal(&self) -> String {
+        format!("&{}", list_literal(self))
+    }
+
+    fn to_env(&self) -> String {
+        self.to_env_delimited(DEFAULT_DELIMITER)
+    }
+
+    fn parse_env(value : &str) -> Result<Self, String> {
+        Self::parse_env_delimited(value, DEFAULT_DELIMITER)
+    }
+
+    fn to_env_delimited(&self, delimiter : &str) -> String {
+        list_env(self, delimiter)
+    }
+
+    fn parse_env_delimited(value : &str, delimiter : &str) -> Result<Self, String> {
+        parse_list(value, delimiter)
+    }
+
+    fn parse_elements(elements : &[String]) -> Result<Self, String> {
+        parse_each(elements)
+    }
+}
+
+impl<T : ConstValue + 'static> ConstValue for &'static [T] {
+    fn const_type() -> String {
+        Vec::<T>::const_type()
+    }
+
+    fn to_literal(&self) -> String {
+        format!("&{}", list_literal(self))
+    }
+
+    fn to_env(&self) -> String {
+        self.to_env_delimited(DEFAULT_DELIMITER)
+    }
+
+    fn parse_env(value : &str) -> Result<Self, String> {
+        Self::parse_env_delimited(value, DEFAULT_DELIMITER)
+    }
+
+    fn to_env_delimited(&self, delimiter : &str) -> String {
+        list_env(self, delimiter)
+    }
+
+    /// The elements are leaked, which is harmless in a build script.
+    fn parse_env_delimited(value : &str, delimiter : &str) -> Result<Self, String> {
+        Ok(Vec::leak(parse_list(value, delimiter)?))
+    }
+
+    /// The elements are leaked, which is harmless in a build script.
+    fn parse_elements(elements : &[String]) -> Result<Self, String> {
+        Ok(Vec::leak(parse_each(elements)?))
+    }
+}
+
+impl<T : ConstValue> ConstValue for Option<T> {
+    fn const_type() -> String {
+        format!("Option<{}>", T::const_type())
+    }
+
+    fn to_literal(&self) -> String {
+        match self {
+            Some(value) => format!("Some({})", value.to_literal()),
+            None => "None".to_string(),
+        }
+    }
+
+    fn to_env(&self) -> String {
+        self.to_env_delimited(DEFAULT_DELIMITER)
+    }
+
+    fn parse_env(value : &str) -> Result<Self, String> {
+        Self::parse_env_delimited(value, DEFAULT_DELIMITER)
+    }
+
+    fn to_env_delimited(&self, delimiter : &str) -> String {
+        match self {
+            Some(value) => value.to_env_delimited(delimiter),
+            None => "none".to_string(),
+        }
+    }
+
+    fn parse_env_delimited(value : &str, delimiter : &str) -> Result<Self, String> {
+        if value.trim().is_empty() || value.trim().eq_ignore_ascii_case("none") {
+            Ok(None)
+        } else {
+            T::parse_env_delimited(value, delimiter).map(Some)
+        }
+    }
+
+    fn parse_elements(elements : &[String]) -> Result<Self, String> {
+        T::parse_elements(elements).map(Some)
+    }
+
+    /// A `Some` default is resolved like `T` unless the variable is set to anything else than `some`,
+    /// so the fields of tuples can be overridden.
+    fn resolve(default : &Self, env_var : &str, delimiter : &str, env : &mut Env) -> Result<Self, NsocError> {
+        match (env(env_var)?, default) {
+            (Some(Override { value, source }), _) if default.is_none() || !value.to_string().trim().eq_ignore_ascii_case("some") => value.parse(delimiter).map_err(|reason| NsocError::InvalidOverride {
+                constant: String::new(), env_var: env_var.to_string(), source, value: value.to_string(), expected: Self::const_type(), default: default.to_literal(), reason }),
+            // `env_var` is already read, so a scalar keeps its default and a tuple is overridden field by field.
+            (_, Some(default)) => T::resolve(default, env_var, delimiter, &mut |name| if name == env_var { Ok(None) } else { env(name) }).map(Some),
+            (_, None) => Ok(None),
+        }
+    }
+
+    fn env_overrides(&self, env_var : &str, delimiter : &str) -> Vec<(String, String)> {
+        match self {
+            Some(value) => value.env_overrides(env_var, delimiter),
+            None => vec![(env_var.to_string(), self.to_env_delimited(delimiter))],
+        }
+    }
+}
+
+/// Implement [ConstValue] for tuples where each field is overridden by its own environment variable.
+macro_rules! tuple_const_value {
+    ($($index : tt : $field_type : ident),+) => {
+        impl<$($field_type : ConstValue),+> ConstValue for ($($field_type,)+) {
+            fn const_type() -> String {
+                tuple_string(vec![$($field_type::const_type()),+])
+            }
+
+            fn to_literal(&self) -> String {
+                tuple_string(vec![$(self.$index.to_literal()),+])
+            }
+
+            fn to_env(&self) -> String {
+                self.to_literal()
+            }
+
+            /// Tuples cannot be parsed from a single variable.
+            fn parse_env(_value : &str) -> Result<Self, String> {
+                Err("tuples are overridden field by field".to_string())
+            }
+
+            fn resolve(default : &Self, env_var : &str, delimiter : &str, env : &mut Env) -> Result<Self, NsocError> {
+                Ok(($($field_type::resolve(&default.$index, &format!("{}_{}", env_var, $index), delimiter, env)?,)+))
+            }
+
+            fn env_overrides(&self, env_var : &str, delimiter : &str) -> Vec<(String, String)> {
+                let mut overrides = Vec::new();
+                $(overrides.extend(self.$index.env_overrides(&format!("{}_{}", env_var, $index), delimiter));)+
+                overrides
+            }
+        }
+    };
+}
+
+tuple_const_value!(0: A);
+tuple_const_value!(0: A, 1: B);
+tuple_const_value!(0: A, 1: B, 2: C);
+tuple_const_value!(0: A, 1: B, 2: C, 3: D);
+tuple_const_value!(0: A, 1: B, 2: C, 3: D, 4: E);
+tuple_const_value!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F);
+tuple_const_value!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G);
+tuple_const_value!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G, 7: H);
+
+/// Write tuple fields between parenthesis, with a trailing comma for a single field.
+fn tuple_string(fields : Vec<String>) -> String {
+    if fields.len() == 1 {
+        format!("({},)", fields[0])
+    } else {
+        format!("({})", fields.join(", "))
+    }
+}
+
+/// Write the elements of a list between brackets.
+fn list_literal<T : ConstValue>(elements : &[T]) -> String {
+    format!("[{}]", elements.iter().map(T::to_literal).collect::<Vec<String>>().join(", "))
+}
+
+/// Write the elements of a list separated by `delimiter`.
+fn list_env<T : ConstValue>(elements : &[T], delimiter : &str) -> String {
+    elements.iter().map(T::to_env).collect::<Vec<String>>().join(delimiter)
+}
+
+/// Parse elements separated by `delimiter`, each trimmed of whitespace. An empty value has no element.
+fn parse_list<T : ConstValue>(value : &str, delimiter : &str) -> Result<Vec<T>, String> {
+    if value.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    value.split(delimiter).enumerate()
+        .map(|(index, element)| T::parse_env(element.trim())
+            .map_err(|err| format!("element {} `{}`: {}", index, element, err)))
+        .collect()
+}
+
+/// Parse each element of a list read from a source, without trimming.
+fn parse_each<T : ConstValue>(elements : &[String]) -> Result<Vec<T>, String> {
+    elements.iter().enumerate()
+        .map(|(index, element)| T::parse_env(element).map_err(|err| format!("element {} `{}`: {}", index, element, err)))
+        .collect()
+}
+
+/// Write a string literal.
+/// 
+/// Strings containing quotes or backslashes are written as raw strings when they have no control
+/// characters, otherwise characters are escaped.
+fn str_literal(value : &str) -> String {
+    if value.contains(['"', '\\']) && !value.chars().any(char::is_control) {
+        // Use one more `#` than the longest sequence of `#` following a quote.
+        let hashes = value.split('"').skip(1)
+            .map(|part| part.chars().take_while(|c| *c == '#').count())
+            .max().unwrap_or(0) + 1;
+        let hashes = "#".repeat(hashes);
+        format!("r{}\"{}\"{}", hashes, value, hashes)
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ integral, ConstValue };
+    use crate::{ NsocError, Override, SourceValue };
+
+    /// Error of an invalid override without constant name.
+    fn invalid(env_var : &str, value : &str, expected : &str, default : &str, reason : &str) -> NsocError {
+        NsocError::InvalidOverride { constant: String::new(), env_var: env_var.to_string(), source: None, value: value.to_string(), 
+            expected: expected.to_string(), default: default.to_string(), reason: reason.to_string() }
+    }
+
+    #[test]
+    fn integer() {
+        assert_eq!(u16::const_type(), "u16");
+        assert_eq!((-42i64).to_literal(), "-42");
+        assert_eq!(i64::parse_env("-42"), Ok(-42));
+        assert_eq!(u8::parse_env("256"), Err("number too large to fit in target type".to_string()));
+        assert_eq!(u8::parse_env("2.56e2"), Err("number too large to fit in target type".to_string()));
+        assert_eq!(u8::parse_env("1.5"), Err("invalid digit found in string".to_string()));
+        assert_eq!(u16::parse_env("4.43e2"), Ok(443));
+    }
+
+    #[test]
+    fn integral_numbers() {
+        for (number, integer) in [("1e3", "1000"), ("1.0", "1"), ("-2.50e1", "-25"), ("1.5E+2", "150"), ("0.0", "0"), ("100e-2", "1"),
+            ("12345678901234567890.0", "12345678901234567890"), ("0.12e2", "12")] {
+            assert_eq!(integral(number).as_deref(), Some(integer), "{}", number);
+        }
+
+        for number in ["12", "-0", "-0.0", "1.5", "-1.5e-3", "15e-1", "1e300", "0.5", "x.0", "é1e-2"] {
+            assert_eq!(integral(number), None, "{}", number);
+        }
+    }
+
+    #[test]
+    fn bool() {
+        assert_eq!(bool::const_type(), "bool");
+        assert_eq!(true.to_literal(), "true");
+        assert_eq!(bool::parse_env("false"), Ok(false));
+        assert!(bool::parse_env("1").is_err());
+    }
+
+    #[test]
+    fn float_literal() {
+        assert_eq!(1.0f32.to_literal(), "1.0f32");
+        assert_eq!((-0.0f64).to_literal(), "-0.0f64");
+        assert_eq!(0.1f64.to_literal(), "0.1f64");
+        assert_eq!(1e20f64.to_literal(), "1e20f64");
+        assert_eq!(f32::MAX.to_literal(), "3.4028235e38f32");
+        assert_eq!(f64::NAN.to_literal(), "f64::NAN");
+        assert_eq!(f32::INFINITY.to_literal(), "f32::INFINITY");
+        assert_eq!(f64::NEG_INFINITY.to_literal(), "f64::NEG_INFINITY");
+    }
+
+    #[test]
+    fn float_round_trip() {
+        for value in [0.0, -0.0, 1.0, 0.1, 1.0 / 3.0, -2.5e-8, 1e300, f64::MAX, f64::MIN_POSITIVE, 5e-324, f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            assert_eq!(f64::parse_env(&value.to_env()).unwrap().to_bits(), value.to_bits(), "{:?}", value);
+        }
+        for value in [0.0, 1.0, 0.1, 16777217.0, f32::MAX, f32::MIN_POSITIVE, 1e-45, f32::NAN] {
+            assert_eq!(f32::parse_env(&value.to_env()).unwrap().to_bits(), value.to_bits(), "{:?}", value);
+        }
+        assert_eq!(f32::parse_env("1"), Ok(1.0));
+        assert_eq!(f32::parse_env("one"), Err("invalid float literal".to_string()));
+    }
+
+    #[test]
+    fn char() {
+        assert_eq!(char::const_type(), "char");
+        assert_eq!('a'.to_literal(), "'a'");
+        assert_eq!('\''.to_literal(), "'\\''");
+        assert_eq!('\n'.to_literal(), "'\\n'");
+        assert_eq!('é'.to_literal(), "'é'");
+        assert_eq!('\u{301}'.to_literal(), "'\\u{301}'");
+        for value in ['a', '\'', '\\', '\n', '\0', '✓', '\u{301}'] {
+            assert_eq!(char::parse_env(&value.to_env()), Ok(value));
+        }
+        assert_eq!(char::parse_env("ab"), Err("too many characters in string".to_string()));
+    }
+
+    #[test]
+    fn string_type() {
+        assert_eq!(String::const_type(), "&'static str");
+        assert_eq!(<&str>::const_type(), "&'static str");
+    }
+
+    #[test]
+    fn string_literal() {
+        assert_eq!("nsoc".to_literal(), "\"nsoc\"");
+        assert_eq!("".to_literal(), "\"\"");
+        assert_eq!(String::from("été ✓").to_literal(), "\"été ✓\"");
+        assert_eq!("line\nbreak\t\u{7}".to_literal(), "\"line\\nbreak\\t\\u{7}\"");
+        assert_eq!("quote \" and \\n\0".to_literal(), "\"quote \\\" and \\\\n\\0\"");
+    }
+
+    #[test]
+    fn string_raw_literal() {
+        assert_eq!("say \"hi\"".to_literal(), "r#\"say \"hi\"\"#");
+        assert_eq!("C:\\Users".to_literal(), "r#\"C:\\Users\"#");
+        assert_eq!("\"## end".to_literal(), "r###\"\"## end\"###");
+    }
+
+    #[test]
+    fn string_env() {
+        assert_eq!(String::parse_env("a \"b\""), Ok("a \"b\"".to_string()));
+        assert_eq!(<&str>::parse_env("https://nickelange.studio"), Ok("https://nickelange.studio"));
+        assert_eq!("log: ".to_env(), "log: ");
+    }
+
+    #[test]
+    fn array() {
+        assert_eq!(<[u16; 3]>::const_type(), "[u16; 3]");
+        assert_eq!(<[&str; 2]>::const_type(), "[&'static str; 2]");
+        assert_eq!([80u16, 443, 8080].to_literal(), "[80, 443, 8080]");
+        assert_eq!(<[u16; 0]>::const_type(), "[u16; 0]");
+        assert_eq!([0u8; 0].to_literal(), "[]");
+        assert_eq!(["a", "b\"c"].to_literal(), "[\"a\", r#\"b\"c\"#]");
+        assert_eq!([1.0f32, 2.5].to_env(), "1.0,2.5");
+        assert_eq!([1u8, 2].to_env_delimited(";"), "1;2");
+    }
+
+    #[test]
+    fn array_parse() {
+        assert_eq!(<[u16; 3]>::parse_env("80,443,8080"), Ok([80, 443, 8080]));
+        assert_eq!(<[u16; 3]>::parse_env_delimited(" 80 ; 443;8080 ", ";"), Ok([80, 443, 8080]));
+        assert_eq!(<[u16; 0]>::parse_env(""), Ok([]));
+        assert_eq!(<[u16; 3]>::parse_env("80,443"), Err("expected 3 elements separated by `,`, found 2".to_string()));
+        assert_eq!(<[u16; 2]>::parse_env("80;443"), Err("element 0 `80;443`: invalid digit found in string".to_string()));
+        assert_eq!(<[u8; 2]>::parse_env("1,x"), Err("element 1 `x`: invalid digit found in string".to_string()));
+    }
+
+    #[test]
+    fn slice() {
+        assert_eq!(Vec::<&str>::const_type(), "&'static [&'static str]");
+        assert_eq!(<&[char]>::const_type(), "&'static [char]");
+        assert_eq!(vec!["info", "warn"].to_literal(), "&[\"info\", \"warn\"]");
+        assert_eq!(Vec::<u8>::new().to_literal(), "&[]");
+        assert_eq!(Vec::<String>::parse_env("a|b c|"), Ok(vec!["a|b c|".to_string()]));
+        assert_eq!(Vec::<String>::parse_env_delimited("a|b c|", "|"), Ok(vec!["a".to_string(), "b c".to_string(), "".to_string()]));
+        assert_eq!(<&[char]>::parse_env("a, b"), Ok(&['a', 'b'][..]));
+        assert_eq!(Vec::<u8>::parse_env("  "), Ok(vec![]));
+    }
+
+    /// Environment where only `vars` are set.
+    fn env<'a>(vars : &'a [(&str, &str)]) -> impl FnMut(&str) -> Result<Option<Override>, NsocError> + 'a {
+        |env_var| Ok(vars.iter().find(|(name, _)| *name == env_var).map(|(_, value)| Override::from(*value)))
+    }
+
+    #[test]
+    fn resolve() {
+        assert_eq!(u8::resolve(&1, "COUNT", ",", &mut env(&[])), Ok(1));
+        assert_eq!(u8::resolve(&1, "COUNT", ",", &mut env(&[("COUNT", "2")])), Ok(2));
+        assert_eq!(<[u8; 2]>::resolve(&[1, 2], "PAIR", ";", &mut env(&[("PAIR", "3;4")])), Ok([3, 4]));
+        assert_eq!(u8::resolve(&1, "COUNT", ",", &mut env(&[("COUNT", "x")])), Err(invalid("COUNT", "x", "u8", "1", "invalid digit found in string")));
+        assert_eq!(1u8.env_overrides("COUNT", ","), vec![("COUNT".to_string(), "1".to_string())]);
+    }
+
+    #[test]
+    fn resolve_elements() {
+        let list = |elements : &[&str]| { let value = SourceValue::List(elements.iter().map(|element| element.to_string()).collect()); move |_ : &str| Ok(Some(Override { value: value.clone(), source: None })) };
+        assert_eq!(<Vec<&str>>::resolve(&vec![], "TARGETS", ",", &mut list(&["a,b", " padded "])), Ok(vec!["a,b", " padded "]));
+        assert_eq!(<[u8; 2]>::resolve(&[1, 2], "PAIR", ",", &mut list(&["3", "4"])), Ok([3, 4]));
+        assert_eq!(<Option<&[u8]>>::resolve(&None, "PORTS", ",", &mut list(&[])), Ok(Some(&[][..])));
+        assert_eq!(<[u8; 2]>::resolve(&[1, 2], "PAIR", ",", &mut list(&["3"])), Err(invalid("PAIR", "[3]", "[u8; 2]", "[1, 2]", "expected 2 elements, found 1")));
+        assert_eq!(<Vec<u8>>::resolve(&vec![], "PORTS", ",", &mut list(&["1", " 2"])), Err(invalid("PORTS", "[1,  2]", "&'static [u8]", "&[]", "element 1 ` 2`: invalid digit found in string")));
+        assert_eq!(u8::resolve(&1, "COUNT", ",", &mut list(&["1"])), Err(invalid("COUNT", "[1]", "u8", "1", "expected a single value, found a list of 1 elements")));
+    }
+
+    #[test]
+    fn tuple() {
+        assert_eq!(<(usize, usize)>::const_type(), "(usize, usize)");
+        assert_eq!(<(u8,)>::const_type(), "(u8,)");
+        assert_eq!((80usize, "a").to_literal(), "(80, \"a\")");
+        assert_eq!((true,).to_literal(), "(true,)");
+        assert!(<(u8, u8)>::parse_env("1,2").is_err());
+    }
+
+    #[test]
+    fn tuple_resolve() {
+        let default = (80usize, (1u8, [2u8, 3]));
+        assert_eq!(<(usize, (u8, [u8; 2]))>::resolve(&default, "SIZE", ",", &mut env(&[])), Ok(default));
+        assert_eq!(<(usize, (u8, [u8; 2]))>::resolve(&default, "SIZE", ",", &mut env(&[("SIZE", "1"), ("SIZE_0", "40"), ("SIZE_1_1", "4,5")])), Ok((40, (1, [4, 5]))));
+        assert_eq!(<(usize, usize)>::resolve(&(1, 2), "SIZE", ",", &mut env(&[("SIZE_1", "-2")])), Err(invalid("SIZE_1", "-2", "usize", "2", "invalid digit found in string")));
+        assert_eq!(default.env_overrides("SIZE", ";"), vec![
+            ("SIZE_0".to_string(), "80".to_string()),
+            ("SIZE_1_0".to_string(), "1".to_string()),
+            ("SIZE_1_1".to_string(), "2;3".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn option() {
+        assert_eq!(Option::<usize>::const_type(), "Option<usize>");
+        assert_eq!(Option::<Option<&str>>::const_type(), "Option<Option<&'static str>>");
+        assert_eq!(Some(1.0f32).to_literal(), "Some(1.0f32)");
+        assert_eq!(Some(Some('a')).to_literal(), "Some(Some('a'))");
+        assert_eq!(Some([1u8, 2]).to_literal(), "Some([1, 2])");
+        assert_eq!(None::<u8>.to_literal(), "None");
+        assert_eq!(None::<u8>.to_env(), "none");
+        assert_eq!(Some(vec![1u8, 2]).to_env_delimited(";"), "1;2");
+    }
+
+    #[test]
+    fn option_parse() {
+        assert_eq!(Option::<usize>::parse_env(""), Ok(None));
+        assert_eq!(Option::<usize>::parse_env("none"), Ok(None));
+        assert_eq!(Option::<usize>::parse_env(" NONE "), Ok(None));
+        assert_eq!(Option::<usize>::parse_env("1024"), Ok(Some(1024)));
+        assert_eq!(Option::<usize>::parse_env("unbounded"), Err("invalid digit found in string".to_string()));
+        assert_eq!(Option::<[u8; 2]>::parse_env_delimited("1;2", ";"), Ok(Some([1, 2])));
+        assert_eq!(Option::<usize>::resolve(&Some(10), "MAX", ",", &mut env(&[("MAX", "")])), Ok(None));
+        assert_eq!(Option::<usize>::resolve(&None, "MAX", ",", &mut env(&[("MAX", "5")])), Ok(Some(5)));
+        assert_eq!(Option::<usize>::resolve(&Some(10), "MAX", ",", &mut env(&[])), Ok(Some(10)));
+        assert_eq!(Option::<&str>::resolve(&Some("a"), "NAME", ",", &mut env(&[("NAME", "some")])), Ok(Some("a")));
+        assert_eq!(Option::<usize>::resolve(&Some(10), "MAX", ",", &mut env(&[("MAX", " Some ")])), Ok(Some(10)));
+    }
+
+    #[test]
+    fn option_tuple() {
+        let default = Some((80usize, 50usize));
+        assert_eq!(<Option<(usize, usize)>>::resolve(&default, "SIZE", ",", &mut env(&[("SIZE_1", "75")])), Ok(Some((80, 75))));
+        assert_eq!(<Option<(usize, usize)>>::resolve(&default, "SIZE", ",", &mut env(&[("SIZE", "Some"), ("SIZE_0", "40")])), Ok(Some((40, 50))));
+        assert_eq!(<Option<(usize, usize)>>::resolve(&default, "SIZE", ",", &mut env(&[("SIZE", "none"), ("SIZE_0", "40")])), Ok(None));
+        assert_eq!(<Option<(usize, usize)>>::resolve(&None, "SIZE", ",", &mut env(&[("SIZE_0", "40")])), Ok(None));
+        assert_eq!(<Option<(usize, usize)>>::resolve(&None, "SIZE", ",", &mut env(&[("SIZE", "40,50")])),
+            Err(invalid("SIZE", "40,50", "Option<(usize, usize)>", "None", "tuples are overridden field by field")));
+        assert_eq!(default.env_overrides("SIZE", ","), vec![("SIZE_0".to_string(), "80".to_string()), ("SIZE_1".to_string(), "50".to_string())]);
+        assert_eq!(None::<(usize, usize)>.env_overrides("SIZE", ","), vec![("SIZE".to_string(), "none".to_string())]);
+    }
+}
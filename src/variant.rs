@@ -0,0 +1,87 @@
+//! Variants of enums declared outside of the build script.
+
+/// Variant of enum `enum_type` chosen among a whitelist of variants.
+/// 
+/// The enum itself is declared in the crate loading the constants, so only the names of its
+/// variants are known by the build script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    enum_type : String,
+    variants : Vec<String>,
+    variant : usize,
+}
+
+impl Variant {
+    /// Create variant `default` of enum `enum_type` which accepts `variants`.
+    /// 
+    /// Returns an error if `default` is not one of `variants`.
+    pub fn new(enum_type : &str, variants : &[&str], default : &str) -> Result<Self, String> {
+        let variant = Variant { enum_type: enum_type.to_string(), variants: variants.iter().map(|v| v.to_string()).collect(), variant: 0 };
+        variant.parse(default, true)
+    }
+
+    /// Type of the enum.
+    pub fn enum_type(&self) -> &str {
+        &self.enum_type
+    }
+
+    /// Name of the variant.
+    pub fn name(&self) -> &str {
+        &self.variants[self.variant]
+    }
+
+    /// Accepted variants names.
+    pub fn variants(&self) -> &[String] {
+        &self.variants
+    }
+
+    /// Rust path of the variant, i.e. `Level::Debug`.
+    pub fn to_literal(&self) -> String {
+        format!("{}::{}", self.enum_type, self.name())
+    }
+
+    /// Parse the variant named `value` among accepted variants.
+    /// 
+    /// If not `case_sensitive`, `value` matches a variant regardless of case when no variant has this exact name.
+    pub fn parse(&self, value : &str, case_sensitive : bool) -> Result<Self, String> {
+        self.variants.iter().position(|variant| variant == value)
+            .or_else(|| if case_sensitive { None } else { self.variants.iter().position(|variant| variant.eq_ignore_ascii_case(value)) })
+            .map(|variant| Variant { variant, ..self.clone() })
+            .ok_or_else(|| format!("unknown variant `{}` of `{}`, accepted variants are {}", value, self.enum_type, self.variants.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Variant;
+
+    /// Variant of an enum named like log levels.
+    fn level(default : &str) -> Variant {
+        Variant::new("Level", &["Trace", "Debug", "Info", "Warn", "Error"], default).unwrap()
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(level("Info").name(), "Info");
+        assert_eq!(level("Info").to_literal(), "Level::Info");
+        assert_eq!(level("Info").variants().len(), 5);
+        assert_eq!(Variant::new("crate::Level", &["Info"], "Info").unwrap().to_literal(), "crate::Level::Info");
+        assert_eq!(Variant::new("Level", &["Info"], "info"), Err("unknown variant `info` of `Level`, accepted variants are Info".to_string()));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(level("Info").parse("Debug", true).unwrap().to_literal(), "Level::Debug");
+        assert_eq!(level("Info").parse("debug", false).unwrap().to_literal(), "Level::Debug");
+        assert_eq!(level("Info").parse("WARN", false).unwrap().name(), "Warn");
+        assert_eq!(level("Info").parse("debug", true), Err("unknown variant `debug` of `Level`, accepted variants are Trace, Debug, Info, Warn, Error".to_string()));
+        assert!(level("Info").parse("verbose", false).is_err());
+    }
+
+    #[test]
+    fn parse_exact_first() {
+        let variant = Variant::new("Mode", &["fast", "Fast"], "fast").unwrap();
+        assert_eq!(variant.parse("Fast", false).unwrap().to_literal(), "Mode::Fast");
+        assert_eq!(variant.parse("FAST", false).unwrap().to_literal(), "Mode::fast");
+    }
+}
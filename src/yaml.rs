@@ -0,0 +1,356 @@
+//! Minimal YAML parser reading override values into a [Source].
+//! 
+//! Supports block mappings nested by indentation, block sequences and single-line flow sequences of
+//! scalars, plain, single-quoted and double-quoted scalars and comments. Anchors, tags, flow mappings
+//! and multi-line scalars are not supported.
+
+use crate::{ Source, SourceValue };
+
+/// Parse the YAML document `content` into `source`, nested keys being joined with `_`.
+/// 
+/// `null`, `~` and empty values are written as empty values so `Option` constants are `None`, booleans
+/// as `true` or `false`, and hexadecimal, octal and special float values like in Rust.
+pub fn parse_yaml(content : &str, source : &mut Source) -> Result<(), String> {
+    let mut parser = Parser::default();
+
+    for (index, line) in content.lines().enumerate() {
+        parser.line(line, source).map_err(|reason| format!("line {}: {}", index + 1, reason))?;
+    }
+
+    parser.finish(source);
+    Ok(())
+}
+
+/// State of the parser between lines.
+#[derive(Default)]
+struct Parser {
+    /// Indentation and key prefix of each mapping containing the current line.
+    levels : Vec<(usize, String)>,
+
+    /// Indentation and full key of the last key without value, which may contain a nested block.
+    open : Option<(usize, String)>,
+
+    /// Indentation, full key and items of the block sequence being read.
+    sequence : Option<(usize, String, Vec<String>)>,
+
+    /// Document ended with `...`.
+    ended : bool,
+}
+
+impl Parser {
+    fn line(&mut self, line : &str, source : &mut Source) -> Result<(), String> {
+        let line = strip_comment(line);
+        let text = line.trim_start_matches(' ');
+        let indent = line.len() - text.len();
+        let text = text.trim_end();
+
+        if text.starts_with('\t') {
+            return Err("tabs are not allowed for indentation".to_string());
+        }
+
+        if text.is_empty() || self.ended || (indent == 0 && text == "---") {
+            return Ok(());
+        }
+
+        if indent == 0 && text == "..." {
+            self.ended = true;
+            return Ok(());
+        }
+
+        if let Some(item) = text.strip_prefix('-').filter(|item| item.is_empty() || item.starts_with(' ')) {
+            return self.item(indent, item.trim_start());
+        }
+
+        if let Some((_, key, items)) = self.sequence.take() {
+            source.insert(&key, SourceValue::List(items));
+        }
+
+        let (key, value) = split_key(text)?;
+
+        match self.open.take() {
+            Some((open_indent, open_key)) if indent > open_indent => self.levels.push((indent, open_key)),
+            Some((_, open_key)) => source.insert(&open_key, SourceValue::Value(String::new())),
+            None => (),
+        }
+
+        if self.levels.is_empty() {
+            self.levels.push((indent, String::new()));
+        }
+
+        while self.levels.last().is_some_and(|(level, _)| *level > indent) {
+            self.levels.pop();
+        }
+
+        let prefix = match self.levels.last() {
+            Some((level, prefix)) if *level == indent => prefix,
+            _ => return Err("invalid indentation".to_string()),
+        };
+        let key = if prefix.is_empty() { key } else { format!("{}_{}", prefix, key) };
+
+        if value.is_empty() {
+            self.open = Some((indent, key));
+        } else if let Some(items) = value.strip_prefix('[') {
+            source.insert(&key, SourceValue::List(flow_sequence(items)?));
+        } else {
+            source.insert(&key, SourceValue::Value(scalar(value)?));
+        }
+
+        Ok(())
+    }
+
+    /// Add the item of a block sequence, which must follow a key without value.
+    fn item(&mut self, indent : usize, item : &str) -> Result<(), String> {
+        if item.starts_with(['[', '-']) || split_key(item).is_ok() {
+            return Err("sequences can only contain scalars".to_string());
+        }
+        let item = scalar(item)?;
+
+        match (&mut self.sequence, self.open.take()) {
+            (Some((sequence_indent, _, items)), _) if *sequence_indent == indent => items.push(item),
+            (None, Some((open_indent, key))) if indent >= open_indent => self.sequence = Some((indent, key, vec![item])),
+            _ => return Err("unexpected sequence item".to_string()),
+        }
+
+        Ok(())
+    }
+
+    /// Insert the value being read at the end of the document.
+    fn finish(&mut self, source : &mut Source) {
+        if let Some((_, key, items)) = self.sequence.take() {
+            source.insert(&key, SourceValue::List(items));
+        }
+
+        if let Some((_, key)) = self.open.take() {
+            source.insert(&key, SourceValue::Value(String::new()));
+        }
+    }
+}
+
+/// Remove the comment of `line`, starting with `#` after a whitespace outside quoted scalars.
+fn strip_comment(line : &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = ' ';
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match quote {
+            None if c == '#' && previous.is_whitespace() => return &line[..position],
+            None if matches!(c, '"' | '\'') && (previous.is_whitespace() || matches!(previous, '[' | ',')) => quote = Some(c),
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some('\'') if c == '\'' && chars.peek().is_some_and(|(_, next)| *next == '\'') => { chars.next(); },
+            Some(q) if c == q => quote = None,
+            _ => (),
+        }
+        previous = c;
+    }
+
+    line
+}
+
+/// Split a mapping line into its key and value.
+fn split_key(text : &str) -> Result<(String, &str), String> {
+    if text.starts_with(['"', '\'']) {
+        let end = closing_quote(text).ok_or_else(|| "unterminated quoted key".to_string())?;
+        let value = text[end + 1..].trim_start().strip_prefix(':').ok_or_else(|| "expected `:` after key".to_string())?;
+        return Ok((scalar(&text[..=end])?, value.trim_start()));
+    }
+
+    if let Some(key) = text.strip_suffix(':') {
+        return Ok((key.trim_end().to_string(), ""));
+    }
+
+    match text.split_once(": ") {
+        Some((key, value)) if !key.is_empty() => Ok((key.trim_end().to_string(), value.trim_start())),
+        _ => Err(format!("expected `key: value`, found `{}`", text)),
+    }
+}
+
+/// Position of the quote closing the quoted scalar at the start of `text`.
+fn closing_quote(text : &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    let mut chars = text.char_indices().skip(1);
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => { chars.next(); },
+            '\'' if quote == '\'' && text[position + 1..].starts_with('\'') => { chars.next(); },
+            c if c == quote => return Some(position),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Parse the items of a flow sequence after its opening bracket.
+fn flow_sequence(items : &str) -> Result<Vec<String>, String> {
+    let items = items.strip_suffix(']').ok_or_else(|| "flow sequences must end on the same line".to_string())?;
+    let mut elements = Vec::new();
+    let mut rest = items.trim();
+
+    while !rest.is_empty() {
+        let end = if rest.starts_with(['"', '\'']) {
+            closing_quote(rest).ok_or_else(|| "unterminated quoted scalar".to_string())? + 1
+        } else {
+            rest.find(',').unwrap_or(rest.len())
+        };
+
+        let element = rest[..end].trim();
+        if element.starts_with(['[', '{']) {
+            return Err("sequences can only contain scalars".to_string());
+        }
+        elements.push(scalar(element)?);
+
+        rest = rest[end..].trim_start();
+        rest = match rest.strip_prefix(',') {
+            Some(rest) => rest.trim_start(),
+            None if rest.is_empty() => rest,
+            None => return Err(format!("expected `,` in flow sequence, found `{}`", rest)),
+        };
+    }
+
+    Ok(elements)
+}
+
+/// Value of a scalar, written like in an environment variable.
+fn scalar(text : &str) -> Result<String, String> {
+    if let Some(quoted) = text.strip_prefix('\'') {
+        return match closing_quote(text) {
+            Some(end) if end == text.len() - 1 => Ok(quoted[..end - 1].replace("''", "'")),
+            _ => Err(format!("invalid quoted scalar `{}`", text)),
+        };
+    }
+
+    if text.starts_with('"') {
+        return match closing_quote(text) {
+            Some(end) if end == text.len() - 1 => unescape(&text[1..end]),
+            _ => Err(format!("invalid quoted scalar `{}`", text)),
+        };
+    }
+
+    if text.starts_with(['&', '*', '!', '|', '>', '{', '@', '`']) {
+        return Err(format!("unsupported value `{}`", text));
+    }
+
+    let radix = |prefix : &str, radix : u32| text.strip_prefix(prefix).and_then(|digits| u128::from_str_radix(digits, radix).ok());
+
+    Ok(match text {
+        "~" | "null" | "Null" | "NULL" => String::new(),
+        "true" | "True" | "TRUE" => "true".to_string(),
+        "false" | "False" | "FALSE" => "false".to_string(),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => "inf".to_string(),
+        "-.inf" | "-.Inf" | "-.INF" => "-inf".to_string(),
+        ".nan" | ".NaN" | ".NAN" => "NaN".to_string(),
+        _ => match radix("0x", 16).or_else(|| radix("0o", 8)) {
+            Some(value) => value.to_string(),
+            None => text.to_string(),
+        },
+    })
+}
+
+/// Replace the escapes of a double-quoted scalar.
+fn unescape(text : &str) -> Result<String, String> {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        unescaped.push(match chars.next() {
+            Some('0') => '\0',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some(c @ ('"' | '\\' | '/' | ' ')) => c,
+            Some(escape @ ('x' | 'u' | 'U')) => {
+                let digits = match escape { 'x' => 2, 'u' => 4, _ => 8 };
+                let hex : String = chars.by_ref().take(digits).collect();
+                u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or_else(|| format!("invalid escape `\\{}{}`", escape, hex))?
+            },
+            Some(c) => return Err(format!("invalid escape `\\{}`", c)),
+            None => return Err("invalid escape at the end of the scalar".to_string()),
+        });
+    }
+
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_yaml;
+    use crate::{ Source, SourceValue };
+
+    #[test]
+    fn values() {
+        let source = Source::parse("nsoc.yaml", "---\n\
+            # Overrides\n\
+            default_width: 300 # Frame\n\
+            hex: 0xFF\n\
+            enabled: True\n\
+            cache: ~\n\
+            ratio: .inf\n\
+            url: https://nickelange.studio/#top\n\
+            single: 'it''s # not a comment'\n\
+            plain: it's # comment\n\
+            double: \"tab\\tquote\\\" \\u00e9\"\n\
+            ports: [80, \"443\", 8080]\n\
+            targets:\n\
+            - stdout\n\
+            - 'file'\n\
+            limits:\n  \
+              max_conn: 10\n  \
+              log:\n    \
+                level: debug\n  \
+              empty:\n\
+            \"quoted key\": 1\n\
+            last:\n\
+            ...\n\
+            ignored: 1\n", parse_yaml).unwrap();
+
+        let get = |key| source.get(key).map(SourceValue::to_string);
+        assert_eq!(get("DEFAULT_WIDTH").as_deref(), Some("300"));
+        assert_eq!(get("HEX").as_deref(), Some("255"));
+        assert_eq!(get("ENABLED").as_deref(), Some("true"));
+        assert_eq!(get("CACHE").as_deref(), Some(""));
+        assert_eq!(get("RATIO").as_deref(), Some("inf"));
+        assert_eq!(get("URL").as_deref(), Some("https://nickelange.studio/#top"));
+        assert_eq!(get("SINGLE").as_deref(), Some("it's # not a comment"));
+        assert_eq!(get("PLAIN").as_deref(), Some("it's"));
+        assert_eq!(get("DOUBLE").as_deref(), Some("tab\tquote\" é"));
+        assert_eq!(get("PORTS").as_deref(), Some("[80, 443, 8080]"));
+        assert_eq!(get("TARGETS").as_deref(), Some("[stdout, file]"));
+        assert_eq!(get("TARGETS_1").as_deref(), Some("file"));
+        assert_eq!(get("LIMITS_MAX_CONN").as_deref(), Some("10"));
+        assert_eq!(get("LIMITS_LOG_LEVEL").as_deref(), Some("debug"));
+        assert_eq!(get("LIMITS_EMPTY").as_deref(), Some(""));
+        assert_eq!(get("QUOTED KEY").as_deref(), Some("1"));
+        assert_eq!(get("LAST").as_deref(), Some(""));
+        assert_eq!(get("IGNORED"), None);
+    }
+
+    #[test]
+    fn indented_sequence() {
+        let source = Source::parse("nsoc.yaml", "ports:\n  - 80\n  - 443\nname: a\n", parse_yaml).unwrap();
+        assert_eq!(source.get("PORTS"), Some(&SourceValue::List(vec!["80".to_string(), "443".to_string()])));
+        assert_eq!(source.get("NAME").map(SourceValue::to_string).as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Source::parse("nsoc.yaml", "a: 1\nb", parse_yaml).unwrap_err(), "line 2: expected `key: value`, found `b`");
+        assert_eq!(Source::parse("nsoc.yaml", "a:\n  b: 1\n c: 2", parse_yaml).unwrap_err(), "line 3: invalid indentation");
+        assert_eq!(Source::parse("nsoc.yaml", "a: 1\n- 2", parse_yaml).unwrap_err(), "line 2: unexpected sequence item");
+        assert_eq!(Source::parse("nsoc.yaml", "a:\n- b: 1", parse_yaml).unwrap_err(), "line 2: sequences can only contain scalars");
+        assert_eq!(Source::parse("nsoc.yaml", "a: [1, [2]]", parse_yaml).unwrap_err(), "line 1: sequences can only contain scalars");
+        assert_eq!(Source::parse("nsoc.yaml", "a: [1,\n 2]", parse_yaml).unwrap_err(), "line 1: flow sequences must end on the same line");
+        assert_eq!(Source::parse("nsoc.yaml", "a: &anchor 1", parse_yaml).unwrap_err(), "line 1: unsupported value `&anchor 1`");
+        assert_eq!(Source::parse("nsoc.yaml", "a: |\n  text", parse_yaml).unwrap_err(), "line 1: unsupported value `|`");
+        assert_eq!(Source::parse("nsoc.yaml", "a: \"open", parse_yaml).unwrap_err(), "line 1: invalid quoted scalar `\"open`");
+        assert_eq!(Source::parse("nsoc.yaml", "\ta: 1", parse_yaml).unwrap_err(), "line 1: tabs are not allowed for indentation");
+    }
+}
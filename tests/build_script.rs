@@ -0,0 +1,299 @@
+//! Build and run the `tests/fixture` crate which use nsoc in its `build.rs`.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Build and run the fixture with the given environment variables.
+/// 
+/// Each `target` is built in its own directory so tests with different variables can run in parallel.
+fn fixture(target: &str, envs: &[(&str, &str)]) -> Output {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixture/Cargo.toml");
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("fixture").join(target);
+
+    Command::new(env!("CARGO"))
+        .args(["run", "--quiet", "--manifest-path"])
+        .arg(&manifest)
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .envs(envs.iter().copied())
+        .output()
+        .expect("Could not run cargo!")
+}
+
+/// Run the fixture with the given environment variables and return its output.
+fn run_fixture(target: &str, envs: &[(&str, &str)]) -> String {
+    let output = fixture(target, envs);
+
+    assert!(output.status.success(), "Fixture failed:\n{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn load_const_includes_generated_files() {
+    let output = run_fixture("default", &[]);
+
+    assert!(output.contains("DEFAULT_WIDTH=150\n"));
+    assert!(output.contains("DEFAULT_HEIGHT=50\n"));
+    assert!(output.contains("MAX_ENTRY=100\n"));
+}
+
+#[test]
+fn string_constants() {
+    let output = run_fixture("default", &[]);
+
+    assert!(output.contains("APP_NAME=\"nsoc\"\n"));
+    assert!(output.contains("BASE_URL=\"https://nickelange.studio/api\"\n"));
+    assert!(output.contains(&format!("ESCAPED={:?}\n", "\"quoted\" C:\\path\n\ttab é ✓ \u{0}")));
+    assert!(output.contains(&format!("RAW={:?}\n", "\"# raw \\ string")));
+}
+
+#[test]
+fn primitive_constants_round_trip() {
+    let output = run_fixture("default", &[]);
+
+    for expected in [
+        format!("FLOAT_ONE={:?} {}", 1.0f32, 1.0f32.to_bits()),
+        format!("FLOAT_THIRD={:?} {}", 1.0f64 / 3.0, (1.0f64 / 3.0).to_bits()),
+        format!("FLOAT_NEG_ZERO={:?} {}", -0.0f64, (-0.0f64).to_bits()),
+        format!("FLOAT_TINY={:?} {}", 5e-324f64, 5e-324f64.to_bits()),
+        format!("FLOAT_MAX={:?} {}", f32::MAX, f32::MAX.to_bits()),
+        format!("FLOAT_NAN={:?}", f64::NAN),
+        format!("FLOAT_INF={:?}", f32::INFINITY),
+        format!("FLOAT_NEG_INF={:?}", f64::NEG_INFINITY),
+        format!("CHAR_QUOTE={:?}", '\''),
+        format!("CHAR_COMBINING={:?}", '\u{301}'),
+        format!("CHAR_NULL={:?}", '\0'),
+        format!("BOOL_TRUE={:?}", true),
+        format!("INT_MIN={:?}", i64::MIN),
+    ] {
+        assert!(output.contains(&format!("{}\n", expected)), "Missing `{}` in:\n{}", expected, output);
+    }
+}
+
+#[test]
+fn list_constants() {
+    let output = run_fixture("default", &[]);
+
+    assert!(output.contains("ALLOWED_PORTS=[80, 443, 8080]\n"));
+    assert!(output.contains("LOG_TARGETS=[\"stdout\", \"file \\\"a\\\"\"]\n"));
+    assert!(output.contains("EMPTY=[]\n"));
+}
+
+#[test]
+fn composite_constants() {
+    let output = run_fixture("default", &[]);
+
+    assert!(output.contains("DEFAULT_SIZE=(80, 50)\n"));
+    assert!(output.contains("LIMITS=Limits { max_conn: 100, timeout_ms: 5000, name: \"default\" }\n"));
+    assert!(output.contains("LOG_LEVEL=Info\n"));
+}
+
+#[test]
+fn composite_constants_overridden_per_field() {
+    let output = run_fixture("composite", &[("FIXTURE_DEFAULT_SIZE_0", "120"), ("FIXTURE_LIMITS_TIMEOUT_MS", "250"), ("FIXTURE_LIMITS_NAME", "ci"), ("FIXTURE_LOG_LEVEL", "warn")]);
+
+    assert!(output.contains("DEFAULT_SIZE=(120, 50)\n"));
+    assert!(output.contains("LIMITS=Limits { max_conn: 100, timeout_ms: 250, name: \"ci\" }\n"));
+    assert!(output.contains("LOG_LEVEL=Warn\n"));
+}
+
+#[test]
+fn option_constants() {
+    let output = run_fixture("default", &[]);
+
+    assert!(output.contains("MAX_CACHE_SIZE=None\n"));
+    assert!(output.contains("CACHE_NAME=Some(\"cache\")\n"));
+    assert!(output.contains("RATIO=Some(1.0)\n"));
+    assert!(output.contains("PORTS=Some([80, 443])\n"));
+}
+
+#[test]
+fn option_constants_overridden() {
+    let output = run_fixture("options", &[("FIXTURE_MAX_CACHE_SIZE", "4096"), ("FIXTURE_CACHE_NAME", ""), ("FIXTURE_RATIO", "None"), ("FIXTURE_PORTS", "8080,8443")]);
+
+    assert!(output.contains("MAX_CACHE_SIZE=Some(4096)\n"));
+    assert!(output.contains("CACHE_NAME=None\n"));
+    assert!(output.contains("RATIO=None\n"));
+    assert!(output.contains("PORTS=Some([8080, 8443])\n"));
+}
+
+#[test]
+fn env_vars_are_prefixed_with_package_name() {
+    let output = run_fixture("prefix", &[("FIXTURE_DEFAULT_WIDTH", "200"), ("DEFAULT_HEIGHT", "75"), ("MAX_ENTRY_COUNT", "7")]);
+
+    assert!(output.contains("DEFAULT_WIDTH=200\n"));
+    assert!(output.contains("DEFAULT_HEIGHT=50\n"));
+    assert!(output.contains("MAX_ENTRY=7\n"));
+}
+
+#[test]
+fn toml_config_overrides_defaults() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("toml");
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("nsoc.toml");
+    let config_path = config.to_str().unwrap();
+
+    std::fs::write(&config, "max_entry_count = 250\n\n[config]\nname = \"toml\"\nsize = [3, 4]\n").unwrap();
+    let output = run_fixture("toml", &[("NSOC_CONFIG", config_path)]);
+    assert!(output.contains("MAX_ENTRY=250\nCONFIG_NAME=\"toml\"\nCONFIG_SIZE=(3, 4)\n"), "{}", output);
+
+    // Environment variables have precedence over the configuration file.
+    let output = run_fixture("toml", &[("NSOC_CONFIG", config_path), ("FIXTURE_CONFIG_NAME", "env")]);
+    assert!(output.contains("MAX_ENTRY=250\nCONFIG_NAME=\"env\"\n"), "{}", output);
+
+    // Editing the file rebuilds the constants.
+    std::fs::write(&config, "[config]\nsize = [5, 6]\n").unwrap();
+    let output = run_fixture("toml", &[("NSOC_CONFIG", config_path)]);
+    assert!(output.contains("MAX_ENTRY=100\nCONFIG_NAME=\"default\"\nCONFIG_SIZE=(5, 6)\n"), "{}", output);
+}
+
+#[test]
+fn dotenv_overrides_config_and_defaults() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("dotenv");
+    std::fs::create_dir_all(&dir).unwrap();
+    let (dotenv, config) = (dir.join(".env"), dir.join("nsoc.toml"));
+    let envs = [("FIXTURE_DOTENV", dotenv.to_str().unwrap()), ("NSOC_CONFIG", config.to_str().unwrap())];
+
+    std::fs::write(&config, "[config]\nname = \"toml\"\nsize = [3, 4]\n").unwrap();
+    std::fs::write(&dotenv, "# Local settings\nexport FIXTURE_CONFIG_NAME=\"dotenv\"\nMAX_ENTRY_COUNT = 7 # Entries\n").unwrap();
+    let output = run_fixture("dotenv", &envs);
+    assert!(output.contains("MAX_ENTRY=7\nCONFIG_NAME=\"dotenv\"\nCONFIG_SIZE=(3, 4)\n"), "{}", output);
+
+    // Editing the file rebuilds the constants.
+    std::fs::write(&dotenv, "CONFIG_SIZE_1=9\n").unwrap();
+    let output = run_fixture("dotenv", &envs);
+    assert!(output.contains("MAX_ENTRY=100\nCONFIG_NAME=\"toml\"\nCONFIG_SIZE=(3, 9)\n"), "{}", output);
+}
+
+#[test]
+fn created_source_file_rebuilds_constants() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("created");
+    std::fs::create_dir_all(&dir).unwrap();
+    let dotenv = dir.join(".env");
+    let _ = std::fs::remove_file(&dotenv);
+    let envs = [("FIXTURE_DOTENV", dotenv.to_str().unwrap()), ("FIXTURE_WATCH_MISSING", "1")];
+
+    assert!(run_fixture("created", &envs).contains("CONFIG_NAME=\"default\"\n"));
+
+    // The file did not exist during the first build.
+    std::fs::write(&dotenv, "CONFIG_NAME=created\n").unwrap();
+    let output = run_fixture("created", &envs);
+    assert!(output.contains("CONFIG_NAME=\"created\"\n"), "{}", output);
+}
+
+#[test]
+fn missing_source_files_are_not_watched() {
+    // `.env`, `nsoc.toml` and `ci.json` of the fixture are missing, the second build must be fresh.
+    run_fixture("fresh", &[]);
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixture/Cargo.toml");
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--verbose", "--manifest-path"])
+        .arg(&manifest)
+        .env("CARGO_TARGET_DIR", PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("fixture").join("fresh"))
+        .output()
+        .expect("Could not run cargo!");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Fresh fixture "), "{}", stderr);
+    assert!(!stderr.contains("Running `"), "{}", stderr);
+}
+
+#[test]
+fn json_overrides_with_native_types() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("json");
+    std::fs::create_dir_all(&dir).unwrap();
+    let json = dir.join("ci.json");
+
+    std::fs::write(&json, r#"{ "allowed_ports": [1, 2, 3], "log": { "targets": ["syslog; local", " file \"b\" "] }, "empty": [0.5] }"#).unwrap();
+    let output = run_fixture("json", &[("FIXTURE_JSON", json.to_str().unwrap())]);
+    assert!(output.contains("ALLOWED_PORTS=[1, 2, 3]\n"), "{}", output);
+    // Elements are neither split by the delimiter `;` nor trimmed.
+    assert!(output.contains("LOG_TARGETS=[\"syslog; local\", \" file \\\"b\\\" \"]\n"), "{}", output);
+    assert!(output.contains("EMPTY=[0.5]\n"), "{}", output);
+}
+
+#[test]
+fn invalid_toml_config_fails_the_build() {
+    let output = fixture("invalid_toml", &[("NSOC_CONFIG", "missing.toml")]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("missing.toml`: file set by NSOC_CONFIG does not exist."), "{}", stderr);
+
+    // Invalid values are reported with the file they were read from.
+    let config = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("invalid_value.toml");
+    std::fs::write(&config, "max_entry_count = \"many\"\n").unwrap();
+    let output = fixture("invalid_toml", &[("NSOC_CONFIG", config.to_str().unwrap())]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains(&format!("Invalid value `many` of `MAX_ENTRY_COUNT` in `{}` for constant `MAX_ENTRY`", config.display())), "{}", stderr);
+}
+
+#[test]
+fn builder_constants() {
+    assert!(run_fixture("default", &[]).contains("BUILDER_WIDTH=150\nBUILDER_HEIGHT=50\n"));
+    assert!(run_fixture("builder", &[("FIXTURE_BUILDER_HEIGHT", "75")]).contains("BUILDER_WIDTH=150\nBUILDER_HEIGHT=75\n"));
+}
+
+#[test]
+fn manifest_metadata_constants() {
+    assert!(run_fixture("default", &[]).contains("METADATA_WIDTH=150\nMETADATA_TARGETS=[\"stdout\", \"file\"]\n"));
+
+    let output = run_fixture("metadata", &[("FIXTURE_METADATA_WIDTH", "300"), ("FIXTURE_METADATA_TARGETS", "stderr")]);
+    assert!(output.contains("METADATA_WIDTH=300\nMETADATA_TARGETS=[\"stderr\"]\n"), "{}", output);
+
+    let output = fixture("metadata", &[("FIXTURE_METADATA_WIDTH", "0")]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("must be >= 1 and <= 4096"), "{}", stderr);
+}
+
+#[test]
+fn workspace_metadata_constants_are_shared() {
+    assert!(run_fixture("default", &[]).contains("MAX_PACKET_SIZE=1500 1500\n"));
+
+    // One variable overrides the constant in every package of the workspace.
+    let output = run_fixture("workspace", &[("SHARED_MAX_PACKET_SIZE", "9000")]);
+    assert!(output.contains("MAX_PACKET_SIZE=9000 9000\n"), "{}", output);
+}
+
+#[test]
+fn profile_defaults() {
+    assert!(run_fixture("default", &[]).contains("LOG_BUFFER=20\n"));
+    assert!(run_fixture("profile", &[("CARGO_PROFILE_DEV_OPT_LEVEL", "1")]).contains("LOG_BUFFER=500\n"));
+
+    // An explicit override has precedence over the default of the profile.
+    assert!(run_fixture("profile", &[("CARGO_PROFILE_DEV_OPT_LEVEL", "1"), ("FIXTURE_LOG_BUFFER", "64")]).contains("LOG_BUFFER=64\n"));
+
+    let generated = std::fs::read_dir(PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("fixture/profile/debug/build")).unwrap()
+        .filter_map(|dir| std::fs::read_to_string(dir.unwrap().path().join("out/config.rs")).ok()).next().unwrap();
+    assert!(generated.contains("/// The default value is chosen for profile `opt-level=1`.\n"), "{}", generated);
+}
+
+#[test]
+fn target_defaults() {
+    // The fixture is built for the host, which is also the target of these tests.
+    let expected = if cfg!(all(target_arch = "x86_64", target_os = "linux")) { 8192 } else { 4096 };
+    assert!(run_fixture("default", &[]).contains(&format!("RX_BUFFER={}\n", expected)));
+    assert!(run_fixture("target", &[("FIXTURE_RX_BUFFER", "512")]).contains("RX_BUFFER=512\n"));
+}
+
+#[test]
+fn env_change_between_builds_changes_constants() {
+    // Same target directory so the second build reuses the compiled build script.
+    assert!(run_fixture("rerun", &[]).contains("DEFAULT_WIDTH=150\n"));
+    assert!(run_fixture("rerun", &[("FIXTURE_DEFAULT_WIDTH", "300")]).contains("DEFAULT_WIDTH=300\n"));
+    assert!(run_fixture("rerun", &[("FIXTURE_DEFAULT_WIDTH", "450")]).contains("DEFAULT_WIDTH=450\n"));
+    assert!(run_fixture("rerun", &[]).contains("DEFAULT_WIDTH=150\n"));
+}
+
+#[test]
+fn invalid_overrides_fail_the_build() {
+    let output = fixture("invalid", &[("FIXTURE_DEFAULT_WIDTH", "wide"), ("FIXTURE_DEFAULT_HEIGHT", "-1")]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // Both errors of the same write_file! block are reported.
+    assert!(!output.status.success());
+    assert!(stderr.contains("2 error(s) while generating constants:"), "{}", stderr);
+    assert!(stderr.contains("cargo:warning=Invalid value `wide` of environment variable `FIXTURE_DEFAULT_WIDTH` for constant `DEFAULT_WIDTH`: invalid digit found in string. Expected `usize`, default is `150`."), "{}", stderr);
+    assert!(stderr.contains("Invalid value `-1` of environment variable `FIXTURE_DEFAULT_HEIGHT` for constant `DEFAULT_HEIGHT`"), "{}", stderr);
+}
@@ -0,0 +1,70 @@
+use nsoc::{write_const, write_file, Const, ConstFile, Visibility};
+
+fn main() {
+    write_file!{ f,
+        write_const!(f, DEFAULT_WIDTH, usize, 150, "Default frame width");
+        write_const!(f, DEFAULT_HEIGHT, usize, 50, "Default frame height")
+    }
+
+    write_file!{ "config", f,
+        f.watch_missing(nsoc::env_override("FIXTURE_WATCH_MISSING").unwrap().is_some());
+        f.dotenv(nsoc::env_override("FIXTURE_DOTENV").unwrap().unwrap_or(String::from(".env")));
+        f.toml("nsoc.toml");
+        write_const!(f, MAX_ENTRY, u32, 100, "Maximum entry count", env = "MAX_ENTRY_COUNT");
+        write_const!(f, CONFIG_NAME, &str, "default", "Configuration name");
+        write_const!(f, CONFIG_SIZE, (u8, u8), (1, 2), "Configuration size");
+        write_const!(f, LOG_BUFFER, usize, 10, "Log buffer size", profile = ("opt-level=1", 500), profile = ("dev", 20), profile = ("release", 10_000));
+        write_const!(f, RX_BUFFER, usize, 4096, "Receive buffer size", target = ("pointer_width=16", 256), target = ("x86_64-*-linux-*", 8192))
+    }
+
+    write_file!{ "strings", f,
+        write_const!(f, APP_NAME, &str, "nsoc", "Application name");
+        write_const!(f, BASE_URL, String, String::from("https://nickelange.studio/api"), "API base url");
+        write_const!(f, ESCAPED, &'static str, "\"quoted\" C:\\path\n\ttab é ✓ \u{0}", "String needing escapes");
+        write_const!(f, RAW, &str, "\"# raw \\ string", "String written as raw string")
+    }
+
+    write_file!{ "primitives", f,
+        write_const!(f, FLOAT_ONE, f32, 1.0, "Integral float");
+        write_const!(f, FLOAT_THIRD, f64, 1.0 / 3.0, "Float needing all digits");
+        write_const!(f, FLOAT_NEG_ZERO, f64, -0.0, "Negative zero");
+        write_const!(f, FLOAT_TINY, f64, 5e-324, "Smallest subnormal");
+        write_const!(f, FLOAT_MAX, f32, f32::MAX, "Largest float");
+        write_const!(f, FLOAT_NAN, f64, f64::NAN, "Not a number");
+        write_const!(f, FLOAT_INF, f32, f32::INFINITY, "Positive infinity");
+        write_const!(f, FLOAT_NEG_INF, f64, f64::NEG_INFINITY, "Negative infinity");
+        write_const!(f, CHAR_QUOTE, char, '\'', "Quote character");
+        write_const!(f, CHAR_COMBINING, char, '\u{301}', "Combining character");
+        write_const!(f, CHAR_NULL, char, '\0', "Null character");
+        write_const!(f, BOOL_TRUE, bool, true, "True boolean");
+        write_const!(f, INT_MIN, i64, i64::MIN, "Smallest integer")
+    }
+
+    write_file!{ "lists", f,
+        f.json(nsoc::env_override("FIXTURE_JSON").unwrap().unwrap_or(String::from("ci.json")));
+        write_const!(f, ALLOWED_PORTS, [u16; 3], [80, 443, 8080], "Allowed ports");
+        write_const!(f, LOG_TARGETS, Vec<&str>, vec!["stdout", "file \"a\""], "Log targets", delimiter = ";");
+        write_const!(f, EMPTY, &[f64], &[], "Empty slice")
+    }
+
+    write_file!{ "composite", f,
+        write_const!(f, DEFAULT_SIZE, (usize, usize), (80, 50), "Default size");
+        write_const!(f, LIMITS, struct crate::Limits { max_conn: u32 = 100, timeout_ms: u64 = 5000, name: &str = "default" }, "Limits");
+        write_const!(f, LOG_LEVEL, enum crate::Level { Trace, Debug, Info, Warn, Error } = Info, "Log level")
+    }
+
+    write_file!{ "options", f,
+        write_const!(f, MAX_CACHE_SIZE, Option<usize>, None, "Maximum cache size");
+        write_const!(f, CACHE_NAME, Option<&str>, Some("cache"), "Cache name");
+        write_const!(f, RATIO, Option<f32>, Some(1.0), "Ratio");
+        write_const!(f, PORTS, Option<[u16; 2]>, Some([80, 443]), "Ports")
+    }
+
+    let mut file = ConstFile::new("builder");
+    for (name, default) in [("BUILDER_WIDTH", 150usize), ("BUILDER_HEIGHT", 50)] {
+        file.constant(Const::new(name, default).doc("Frame size declared without macros").visibility(Visibility::Crate));
+    }
+    file.write().unwrap_or_else(|errors| nsoc::report(&errors));
+
+    nsoc::generate();
+}
@@ -0,0 +1,4 @@
+fn main() {
+    // Only the constants shared by the workspace.
+    nsoc::generate();
+}
@@ -0,0 +1 @@
+nsoc::load_const!();
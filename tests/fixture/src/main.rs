@@ -0,0 +1,69 @@
+nsoc::load_const!();
+nsoc::load_const!("config", mod config);
+nsoc::load_const!("strings", mod strings);
+nsoc::load_const!("primitives", mod primitives);
+nsoc::load_const!("lists", mod lists);
+nsoc::load_const!("composite", mod composite);
+nsoc::load_const!("options", mod options);
+nsoc::load_const!("builder", mod builder);
+nsoc::load_const!("metadata", mod metadata);
+
+/// Struct written as a literal by `build.rs`.
+#[derive(Debug)]
+pub struct Limits {
+    pub max_conn: u32,
+    pub timeout_ms: u64,
+    pub name: &'static str,
+}
+
+/// Enum which variant is chosen by `build.rs`.
+#[derive(Debug)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+fn main() {
+    println!("DEFAULT_WIDTH={}", DEFAULT_WIDTH);
+    println!("DEFAULT_HEIGHT={}", DEFAULT_HEIGHT);
+    println!("MAX_ENTRY={}", config::MAX_ENTRY);
+    println!("CONFIG_NAME={:?}", config::CONFIG_NAME);
+    println!("CONFIG_SIZE={:?}", config::CONFIG_SIZE);
+    println!("LOG_BUFFER={}", config::LOG_BUFFER);
+    println!("RX_BUFFER={}", config::RX_BUFFER);
+    println!("APP_NAME={:?}", strings::APP_NAME);
+    println!("BASE_URL={:?}", strings::BASE_URL);
+    println!("ESCAPED={:?}", strings::ESCAPED);
+    println!("RAW={:?}", strings::RAW);
+    println!("FLOAT_ONE={:?} {}", primitives::FLOAT_ONE, primitives::FLOAT_ONE.to_bits());
+    println!("FLOAT_THIRD={:?} {}", primitives::FLOAT_THIRD, primitives::FLOAT_THIRD.to_bits());
+    println!("FLOAT_NEG_ZERO={:?} {}", primitives::FLOAT_NEG_ZERO, primitives::FLOAT_NEG_ZERO.to_bits());
+    println!("FLOAT_TINY={:?} {}", primitives::FLOAT_TINY, primitives::FLOAT_TINY.to_bits());
+    println!("FLOAT_MAX={:?} {}", primitives::FLOAT_MAX, primitives::FLOAT_MAX.to_bits());
+    println!("FLOAT_NAN={:?}", primitives::FLOAT_NAN);
+    println!("FLOAT_INF={:?}", primitives::FLOAT_INF);
+    println!("FLOAT_NEG_INF={:?}", primitives::FLOAT_NEG_INF);
+    println!("CHAR_QUOTE={:?}", primitives::CHAR_QUOTE);
+    println!("CHAR_COMBINING={:?}", primitives::CHAR_COMBINING);
+    println!("CHAR_NULL={:?}", primitives::CHAR_NULL);
+    println!("BOOL_TRUE={:?}", primitives::BOOL_TRUE);
+    println!("INT_MIN={:?}", primitives::INT_MIN);
+    println!("ALLOWED_PORTS={:?}", lists::ALLOWED_PORTS);
+    println!("LOG_TARGETS={:?}", lists::LOG_TARGETS);
+    println!("EMPTY={:?}", lists::EMPTY);
+    println!("DEFAULT_SIZE={:?}", composite::DEFAULT_SIZE);
+    println!("LIMITS={:?}", composite::LIMITS);
+    println!("LOG_LEVEL={:?}", composite::LOG_LEVEL);
+    println!("MAX_CACHE_SIZE={:?}", options::MAX_CACHE_SIZE);
+    println!("CACHE_NAME={:?}", options::CACHE_NAME);
+    println!("RATIO={:?}", options::RATIO);
+    println!("PORTS={:?}", options::PORTS);
+    println!("BUILDER_WIDTH={}", builder::BUILDER_WIDTH);
+    println!("BUILDER_HEIGHT={}", builder::BUILDER_HEIGHT);
+    println!("METADATA_WIDTH={}", metadata::METADATA_WIDTH);
+    println!("METADATA_TARGETS={:?}", metadata::METADATA_TARGETS);
+    println!("MAX_PACKET_SIZE={} {}", metadata::MAX_PACKET_SIZE, fixture_net::MAX_PACKET_SIZE);
+}